@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+
+/// Held messages kept at most; Twitch expires holds after a few minutes anyway
+const MAX_HELD_MESSAGES: usize = 500;
+
+/// A chat message AutoMod is holding for moderator review
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HeldMessage {
+    pub message_id: String,
+    pub broadcaster_user_id: String,
+    pub broadcaster_user_login: String,
+    pub user_id: String,
+    pub user_login: String,
+    pub user_name: String,
+    pub text: String,
+    /// AutoMod category, e.g. `swearing`
+    pub category: String,
+    pub level: u32,
+    /// RFC 3339 time the message was held
+    pub held_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AutomodStatus {
+    Approved,
+    Denied,
+    Expired,
+}
+
+/// Outcome of a held message, sent with the `automod-resolved` event
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AutomodResolution {
+    pub message_id: String,
+    pub broadcaster_user_id: String,
+    pub status: AutomodStatus,
+    /// Moderator who handled it, `None` when it expired
+    pub moderator_login: Option<String>,
+}
+
+/// Messages waiting for review across the channels we moderate, oldest first
+#[derive(Default)]
+pub struct AutomodQueue {
+    held: Vec<HeldMessage>,
+}
+
+impl AutomodQueue {
+    /// Queue an `automod.message.hold` EventSub event
+    pub fn add_hold(&mut self, event: &serde_json::Value) -> Option<HeldMessage> {
+        let field = |name: &str| event.get(name).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+        let message_id = field("message_id");
+        if message_id.is_empty() || self.held.iter().any(|m| m.message_id == message_id) {
+            return None;
+        }
+        let held = HeldMessage {
+            message_id,
+            broadcaster_user_id: field("broadcaster_user_id"),
+            broadcaster_user_login: field("broadcaster_user_login"),
+            user_id: field("user_id"),
+            user_login: field("user_login"),
+            user_name: field("user_name"),
+            text: event.get("message").and_then(|m| m.get("text")).and_then(|t| t.as_str()).unwrap_or_default().to_string(),
+            category: field("category"),
+            level: event.get("level").and_then(|l| l.as_u64()).unwrap_or(0) as u32,
+            held_at: field("held_at"),
+        };
+        self.held.push(held.clone());
+        if self.held.len() > MAX_HELD_MESSAGES {
+            self.held.remove(0);
+        }
+        Some(held)
+    }
+
+    /// Remove a message from the queue, `None` if it was already resolved
+    pub fn resolve(&mut self, message_id: &str, status: AutomodStatus, moderator_login: Option<String>) -> Option<AutomodResolution> {
+        let index = self.held.iter().position(|m| m.message_id == message_id)?;
+        let held = self.held.remove(index);
+        Some(AutomodResolution {
+            message_id: held.message_id,
+            broadcaster_user_id: held.broadcaster_user_id,
+            status,
+            moderator_login,
+        })
+    }
+
+    /// Resolve from an `automod.message.update` EventSub event
+    pub fn apply_update(&mut self, event: &serde_json::Value) -> Option<AutomodResolution> {
+        let status = match event.get("status").and_then(|s| s.as_str())?.to_lowercase().as_str() {
+            "approved" => AutomodStatus::Approved,
+            "denied" => AutomodStatus::Denied,
+            "expired" => AutomodStatus::Expired,
+            _ => return None,
+        };
+        let moderator_login = event.get("moderator_user_login").and_then(|m| m.as_str())
+            .filter(|m| !m.is_empty())
+            .map(|m| m.to_string());
+        self.resolve(event.get("message_id").and_then(|id| id.as_str())?, status, moderator_login)
+    }
+
+    /// Held messages, optionally only those of one channel
+    pub fn list(&self, broadcaster_id: Option<&str>) -> Vec<HeldMessage> {
+        self.held.iter()
+            .filter(|m| broadcaster_id.is_none_or(|id| m.broadcaster_user_id == id))
+            .cloned()
+            .collect()
+    }
+}
@@ -1,167 +1,1126 @@
 use futures_util::{SinkExt, StreamExt};
 use log::{info, error};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
 use tauri::{Emitter, Window};
 use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
-use tokio::sync::mpsc;
-
+use tokio::sync::{mpsc, oneshot, Mutex};
+use crate::chat_log::ChatLog;
+use crate::chatters::{Chatter, ChatterRole};
+use crate::history::ChatHistory;
+use crate::ratelimit::RateLimiter;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ChatMessage {
     pub id: String,
+    /// Display name
     pub user: String,
+    /// Lowercase login, from the IRC prefix
+    pub login: String,
+    pub user_id: String,
     pub message: String,
     pub color: Option<String>,
     pub badges: Vec<(String, String)>,
+    /// Extra badge details, e.g. exact subscription months as `("subscriber", "14")`
+    pub badge_info: Vec<(String, String)>,
+    pub channel: String,
+    /// Unix time in milliseconds, from `tmi-sent-ts` when Twitch provides it
+    pub timestamp: i64,
+    /// Set when the message answers another one
+    pub reply: Option<ReplyInfo>,
+    /// `/me` message, shown in the user's color
+    pub is_action: bool,
+    /// Sent before we joined, loaded from the recent-messages backfill
+    #[serde(default)]
+    pub historical: bool,
+    /// Raw IRC tags, kept for logs and exports; empty for our own echoed messages
+    #[serde(default)]
+    pub tags: HashMap<String, String>,
+}
+
+/// The message a reply answers, and the thread it belongs to
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ReplyInfo {
+    pub parent_msg_id: String,
+    pub parent_user_login: Option<String>,
+    pub parent_display_name: Option<String>,
+    pub parent_msg_body: Option<String>,
+    /// First message of the thread; replies to replies share it
+    pub thread_parent_msg_id: String,
+    pub thread_parent_user_login: Option<String>,
+}
+
+impl ReplyInfo {
+    fn from_tags(tags: &HashMap<String, String>) -> Option<Self> {
+        let non_empty = |key: &str| tags.get(key).filter(|v| !v.is_empty()).cloned();
+        let parent_msg_id = non_empty("reply-parent-msg-id")?;
+        Some(ReplyInfo {
+            thread_parent_msg_id: non_empty("reply-thread-parent-msg-id").unwrap_or_else(|| parent_msg_id.clone()),
+            thread_parent_user_login: non_empty("reply-thread-parent-user-login"),
+            parent_user_login: non_empty("reply-parent-user-login"),
+            parent_display_name: non_empty("reply-parent-display-name"),
+            parent_msg_body: non_empty("reply-parent-msg-body"),
+            parent_msg_id,
+        })
+    }
+}
+
+/// Number of reply threads kept per session
+const MAX_REPLY_THREADS: usize = 500;
+
+/// Replies seen this session, grouped by the first message of their thread
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReplyThread {
+    pub channel: String,
+    pub thread_parent_msg_id: String,
+    pub thread_parent_user_login: Option<String>,
+    pub replies: Vec<ChatMessage>,
+}
+
+#[derive(Default)]
+pub struct ReplyThreads {
+    threads: HashMap<String, ReplyThread>,
+    /// Thread ids, oldest first, for eviction
+    order: VecDeque<String>,
+}
+
+impl ReplyThreads {
+    fn add(&mut self, message: &ChatMessage) {
+        let Some(reply) = &message.reply else { return };
+        let thread_id = reply.thread_parent_msg_id.clone();
+        if !self.threads.contains_key(&thread_id) {
+            if self.order.len() >= MAX_REPLY_THREADS {
+                if let Some(oldest) = self.order.pop_front() {
+                    self.threads.remove(&oldest);
+                }
+            }
+            self.order.push_back(thread_id.clone());
+        }
+        self.threads.entry(thread_id.clone())
+            .or_insert_with(|| ReplyThread {
+                channel: message.channel.clone(),
+                thread_parent_msg_id: thread_id,
+                thread_parent_user_login: reply.thread_parent_user_login.clone(),
+                replies: Vec::new(),
+            })
+            .replies.push(message.clone());
+    }
+
+    pub fn get(&self, thread_id: &str) -> Option<&ReplyThread> {
+        self.threads.get(thread_id)
+    }
+
+    /// Reply metadata for answering `parent_id`, filled from the parent message when we still have it,
+    /// either as `parent` or among the replies of a thread
+    fn reply_to(&self, parent_id: &str, parent: Option<&ChatMessage>) -> ReplyInfo {
+        let parent = parent.or_else(|| self.threads.values()
+            .flat_map(|t| t.replies.iter())
+            .find(|m| m.id == parent_id));
+        let thread = self.threads.get(parent_id)
+            .or_else(|| parent.and_then(|p| p.reply.as_ref()).and_then(|r| self.threads.get(&r.thread_parent_msg_id)));
+        ReplyInfo {
+            parent_msg_id: parent_id.to_string(),
+            parent_user_login: parent.map(|p| p.login.clone()).filter(|login| !login.is_empty()),
+            parent_display_name: parent.map(|p| p.user.clone()),
+            parent_msg_body: parent.map(|p| p.message.clone()),
+            thread_parent_msg_id: thread.map(|t| t.thread_parent_msg_id.clone()).unwrap_or_else(|| parent_id.to_string()),
+            thread_parent_user_login: thread.and_then(|t| t.thread_parent_user_login.clone()),
+        }
+    }
+}
+
+/// Chat modes of a channel, as announced by ROOMSTATE
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RoomState {
     pub channel: String,
+    pub room_id: Option<String>,
+    pub emote_only: bool,
+    /// Minimum follow time in minutes, `None` when followers-only mode is off
+    pub followers_only: Option<i64>,
+    pub r9k: bool,
+    /// Seconds between messages, 0 when slow mode is off
+    pub slow: u32,
+    pub subs_only: bool,
+    /// Unix timestamp (ms) of our last message in this channel, for slow-mode countdowns
+    pub last_sent_at: Option<i64>,
+}
+
+impl RoomState {
+    /// Apply ROOMSTATE tags. Twitch sends every tag on join but only the changed ones afterwards.
+    fn apply_tags(&mut self, tags: &HashMap<String, String>) {
+        if let Some(id) = tags.get("room-id") {
+            self.room_id = Some(id.clone());
+        }
+        if let Some(v) = tags.get("emote-only") {
+            self.emote_only = v == "1";
+        }
+        if let Some(v) = tags.get("followers-only") {
+            self.followers_only = v.parse::<i64>().ok().filter(|m| *m >= 0);
+        }
+        if let Some(v) = tags.get("r9k") {
+            self.r9k = v == "1";
+        }
+        if let Some(v) = tags.get("slow") {
+            self.slow = v.parse().unwrap_or(0);
+        }
+        if let Some(v) = tags.get("subs-only") {
+            self.subs_only = v == "1";
+        }
+    }
+}
+
+/// Our own chat identity, from GLOBALUSERSTATE (global) or USERSTATE (per channel)
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct UserState {
+    /// Channel login, `None` for the global state
+    pub channel: Option<String>,
+    pub user_id: Option<String>,
+    pub display_name: Option<String>,
+    pub color: Option<String>,
+    pub badges: Vec<(String, String)>,
+    pub badge_info: Vec<(String, String)>,
+    pub emote_sets: Vec<String>,
+    pub is_mod: bool,
+}
+
+impl UserState {
+    /// Broadcasters, moderators and VIPs get higher rate limits and skip slow mode
+    pub fn is_privileged(&self) -> bool {
+        self.is_mod || self.badges.iter().any(|(set, _)| matches!(set.as_str(), "broadcaster" | "moderator" | "vip"))
+    }
+
+    /// Broadcasters and moderators can use moderator-only Helix endpoints
+    pub fn is_moderator(&self) -> bool {
+        self.is_mod || self.badges.iter().any(|(set, _)| matches!(set.as_str(), "broadcaster" | "moderator"))
+    }
+
+    fn apply_tags(&mut self, tags: &HashMap<String, String>) {
+        if let Some(id) = tags.get("user-id") {
+            self.user_id = Some(id.clone());
+        }
+        if let Some(name) = tags.get("display-name").filter(|s| !s.is_empty()) {
+            self.display_name = Some(name.clone());
+        }
+        if let Some(color) = tags.get("color") {
+            self.color = Some(color.clone()).filter(|s| !s.is_empty());
+        }
+        if let Some(badges) = tags.get("badges") {
+            self.badges = parse_badges(badges);
+        }
+        if let Some(info) = tags.get("badge-info") {
+            self.badge_info = parse_badges(info);
+        }
+        if let Some(sets) = tags.get("emote-sets") {
+            self.emote_sets = sets.split(',').filter(|s| !s.is_empty()).map(|s| s.to_string()).collect();
+        }
+        if let Some(m) = tags.get("mod") {
+            self.is_mod = m == "1";
+        }
+    }
+}
+
+/// Messages kept per user and channel for user cards
+pub const MAX_USER_MESSAGES: usize = 50;
+
+/// How long we wait for Twitch to acknowledge or reject a sent message
+pub const SEND_ACK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Outcome of a sent chat message, resolved from the USERSTATE or NOTICE that follows it
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum SendResult {
+    Sent { message_id: String },
+    Failed { reason: SendFailure, message: String },
+    /// A slash command was run through Helix instead of being sent as a message
+    Executed { message: String },
+}
+
+impl SendResult {
+    pub fn failed(reason: SendFailure, message: &str) -> Self {
+        SendResult::Failed { reason, message: message.to_string() }
+    }
+}
+
+/// Why Twitch refused a message
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SendFailure {
+    RateLimited,
+    Duplicate,
+    Banned,
+    TimedOut,
+    Warned,
+    Suspended,
+    ChannelSuspended,
+    FollowersOnly,
+    SubsOnly,
+    EmoteOnly,
+    SlowMode,
+    UniqueChat,
+    VerifiedOnly,
+    /// Held by AutoMod for review
+    AutomodHeld,
+    /// Contains a term blocked by the channel
+    BlockedTerm,
+    Rejected,
+    /// Twitch neither acknowledged nor rejected the message in time
+    NoResponse,
+    /// The connection closed before Twitch answered
+    Disconnected,
+}
+
+impl SendFailure {
+    /// Map a NOTICE `msg-id` (or Helix drop code, which uses the same values) to a send failure,
+    /// `None` for notices unrelated to sending
+    pub fn from_msg_id(msg_id: &str) -> Option<Self> {
+        let reason = match msg_id {
+            "msg_ratelimit" => SendFailure::RateLimited,
+            "msg_duplicate" => SendFailure::Duplicate,
+            "msg_banned" => SendFailure::Banned,
+            "msg_timedout" => SendFailure::TimedOut,
+            "msg_warned" => SendFailure::Warned,
+            "msg_suspended" => SendFailure::Suspended,
+            "msg_channel_suspended" => SendFailure::ChannelSuspended,
+            "msg_followersonly" | "msg_followersonly_followed" | "msg_followersonly_zero" => SendFailure::FollowersOnly,
+            "msg_subsonly" => SendFailure::SubsOnly,
+            "msg_emoteonly" => SendFailure::EmoteOnly,
+            "msg_slowmode" => SendFailure::SlowMode,
+            "msg_r9k" => SendFailure::UniqueChat,
+            "msg_verified_email" | "msg_requires_verified_phone_number" => SendFailure::VerifiedOnly,
+            "msg_rejected" => SendFailure::AutomodHeld,
+            "msg_rejected_mandatory" => SendFailure::BlockedTerm,
+            "msg_bad_characters" | "msg_channel_blocked" => SendFailure::Rejected,
+            _ => return None,
+        };
+        Some(reason)
+    }
 }
 
+/// A message queued by `send_chat_message`
+pub struct OutgoingMessage {
+    /// Identifies the message in `chat-send-queued` events
+    pub nonce: String,
+    pub channel: String,
+    pub text: String,
+    /// Message this one replies to
+    pub reply_parent_id: Option<String>,
+    pub responder: oneshot::Sender<SendResult>,
+}
+
+/// A message held back by the rate limiter, emitted as `chat-send-queued`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QueuedMessageStatus {
+    pub nonce: String,
+    pub channel: String,
+    /// 1-based position in the outgoing queue
+    pub position: usize,
+    /// Estimated wait before the message can be written
+    pub wait_ms: u64,
+}
+
+/// Requests handled by the connection task
+pub enum ChatCommand {
+    Join(String),
+    Part(String),
+    Send(OutgoingMessage),
+}
+
+/// A NOTICE from Twitch, emitted as `chat-notice`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChatNotice {
+    /// Channel login, `None` for connection-wide notices
+    pub channel: Option<String>,
+    pub msg_id: Option<String>,
+    pub message: String,
+}
+
+/// A message written to IRC that Twitch has not acknowledged yet
+struct PendingMessage {
+    channel: String,
+    text: String,
+    reply_parent_id: Option<String>,
+    sent_at: std::time::Instant,
+    responder: oneshot::Sender<SendResult>,
+}
+
+/// Chat state shared between the IRC tasks and Tauri commands
+#[derive(Default)]
+pub struct ChatState {
+    /// Channels we want to be in, rejoined after every reconnect
+    pub joined: BTreeSet<String>,
+    /// Room state per channel login
+    pub rooms: HashMap<String, RoomState>,
+    /// Our identity across all channels
+    pub global_user_state: UserState,
+    /// Our identity per channel login
+    pub user_states: HashMap<String, UserState>,
+    /// Whether the IRC connection is currently registered with Twitch
+    pub connected: bool,
+    /// Reply threads seen this session
+    pub threads: ReplyThreads,
+    /// Users seen talking per channel login, by login
+    pub chatters: HashMap<String, HashMap<String, Chatter>>,
+    /// Latest messages per channel login then user login, for user cards
+    pub user_messages: HashMap<String, HashMap<String, VecDeque<ChatMessage>>>,
+    /// Recent messages per channel, kept after leaving so switching back can repopulate
+    pub history: ChatHistory,
+    /// Persistent log messages are recorded to, `None` when logging is off
+    pub chat_log: Option<ChatLog>,
+    /// Sent messages waiting for their USERSTATE or NOTICE, oldest first
+    pending: VecDeque<PendingMessage>,
+}
+
+impl ChatState {
+    /// Resolve the messages Twitch never answered as `NoResponse`
+    fn expire_pending(&mut self) {
+        while self.pending.front().is_some_and(|p| p.sent_at.elapsed() >= SEND_ACK_TIMEOUT) {
+            if let Some(p) = self.pending.pop_front() {
+                let _ = p.responder.send(SendResult::failed(SendFailure::NoResponse, "No response from Twitch"));
+            }
+        }
+    }
+
+    /// Resolve every unacknowledged message as failed, once the connection they went out on is gone
+    pub fn fail_pending(&mut self) {
+        for p in self.pending.drain(..) {
+            let _ = p.responder.send(SendResult::failed(SendFailure::Disconnected, "Chat connection lost"));
+        }
+    }
+
+    /// Forget our badges, color and roles, which belong to the account of a closed connection.
+    /// Anonymous connections never get a USERSTATE to replace them.
+    pub fn forget_identity(&mut self) {
+        self.global_user_state = UserState::default();
+        self.user_states.clear();
+    }
+
+    /// Remember the author of a message for the chatters list and user cards
+    fn observe(&mut self, message: &ChatMessage) {
+        if message.login.is_empty() {
+            return;
+        }
+        let messages = self.user_messages.entry(message.channel.clone()).or_default()
+            .entry(message.login.clone()).or_default();
+        messages.push_back(message.clone());
+        if messages.len() > MAX_USER_MESSAGES {
+            messages.pop_front();
+        }
+        self.chatters.entry(message.channel.clone()).or_default().insert(message.login.clone(), Chatter {
+            user_id: message.user_id.clone(),
+            login: message.login.clone(),
+            display_name: message.user.clone(),
+            role: ChatterRole::from_badges(&message.badges),
+        });
+    }
+
+    /// Add messages from before we joined to the history, chatters, reply threads and chat log
+    pub fn backfill(&mut self, channel: &str, messages: Vec<ChatMessage>) -> usize {
+        let added = self.history.backfill(channel, messages);
+        for message in &added {
+            if let Some(log) = &self.chat_log {
+                log.record(message);
+            }
+            self.observe(message);
+            if message.reply.is_some() {
+                self.threads.add(message);
+            }
+        }
+        added.len()
+    }
+
+    /// Login of a joined channel from its room id
+    pub fn channel_login(&self, room_id: &str) -> Option<String> {
+        self.rooms.values().find(|r| r.room_id.as_deref() == Some(room_id)).map(|r| r.channel.clone())
+    }
+
+    /// Messages `login` sent in `channel` this session, oldest first
+    pub fn messages_from(&self, channel: &str, login: &str) -> Vec<ChatMessage> {
+        self.user_messages.get(channel).and_then(|users| users.get(login))
+            .map(|messages| messages.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Apply a new color of ours everywhere and return the updated user states, global first
+    pub fn set_own_color(&mut self, color: &str) -> Vec<UserState> {
+        self.global_user_state.color = Some(color.to_string());
+        let mut updated = vec![self.global_user_state.clone()];
+        for user_state in self.user_states.values_mut() {
+            user_state.color = Some(color.to_string());
+            updated.push(user_state.clone());
+        }
+        updated
+    }
+
+    /// Take the oldest unacknowledged message for a channel
+    fn take_pending(&mut self, channel: &str) -> Option<PendingMessage> {
+        self.expire_pending();
+        let index = self.pending.iter().position(|p| p.channel == channel)?;
+        self.pending.remove(index)
+    }
+
+    /// Our state in a channel, falling back to the global state before the first USERSTATE
+    pub fn user_state(&self, channel: &str) -> UserState {
+        let mut user_state = self.user_states.get(channel).cloned().unwrap_or_else(|| UserState {
+            channel: Some(channel.to_string()),
+            ..self.global_user_state.clone()
+        });
+        if user_state.user_id.is_none() {
+            user_state.user_id = self.global_user_state.user_id.clone();
+        }
+        user_state
+    }
+
+    /// Build the local copy of a message we sent, since Twitch does not echo our own PRIVMSGs
+    fn build_echo(&self, channel: &str, id: String, text: String, reply_parent_id: Option<String>, username: Option<&str>) -> ChatMessage {
+        let user_state = self.user_state(channel);
+        let (text, is_action) = split_action(&text);
+        let login = username.map(|u| u.to_lowercase())
+            .or_else(|| user_state.display_name.as_ref().map(|d| d.to_lowercase()))
+            .unwrap_or_default();
+        ChatMessage {
+            id,
+            user: user_state.display_name
+                .or_else(|| username.map(|u| u.to_string()))
+                .unwrap_or_else(|| "Unknown".to_string()),
+            login,
+            user_id: user_state.user_id.unwrap_or_default(),
+            message: text,
+            is_action,
+            color: user_state.color,
+            badges: user_state.badges,
+            badge_info: user_state.badge_info,
+            channel: channel.to_string(),
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            reply: reply_parent_id.map(|parent_id| self.threads.reply_to(&parent_id, self.history.find(channel, &parent_id))),
+            historical: false,
+            tags: HashMap::new(),
+        }
+    }
+}
+
+pub type SharedChatState = Arc<Mutex<ChatState>>;
+
+/// A single IRC line split into tags, command and parameters
+struct IrcLine<'a> {
+    tags: HashMap<String, String>,
+    command: &'a str,
+    /// Nick from the prefix (`:nick!user@host`)
+    nick: Option<&'a str>,
+    /// Channel login (without `#`) when the first parameter is a channel
+    channel: Option<&'a str>,
+    /// Trailing parameter (after ` :`)
+    trailing: Option<&'a str>,
+}
+
+fn parse_irc_line(line: &str) -> Option<IrcLine<'_>> {
+    let mut rest = line.trim_end_matches(['\r', '\n']);
+
+    let mut tags = HashMap::new();
+    if let Some(stripped) = rest.strip_prefix('@') {
+        let (raw_tags, after) = stripped.split_once(' ')?;
+        for tag in raw_tags.split(';') {
+            let (key, value) = tag.split_once('=').unwrap_or((tag, ""));
+            tags.insert(key.to_string(), unescape_tag_value(value));
+        }
+        rest = after;
+    }
+
+    let mut nick = None;
+    if let Some(stripped) = rest.strip_prefix(':') {
+        let (prefix, after) = stripped.split_once(' ').unwrap_or((stripped, ""));
+        nick = prefix.split(['!', '@']).next().filter(|n| !n.is_empty());
+        rest = after;
+    }
+
+    let (command, params) = rest.split_once(' ').unwrap_or((rest, ""));
+    if command.is_empty() {
+        return None;
+    }
+
+    let (middle, trailing) = if let Some(t) = params.strip_prefix(':') {
+        ("", Some(t))
+    } else if let Some((m, t)) = params.split_once(" :") {
+        (m, Some(t))
+    } else {
+        (params, None)
+    };
+
+    let channel = middle.split(' ').next().and_then(|p| p.strip_prefix('#'));
+
+    Some(IrcLine { tags, command, nick, channel, trailing })
+}
+
+/// Unescape an IRCv3 tag value
+fn unescape_tag_value(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some(':') => out.push(';'),
+            Some('s') => out.push(' '),
+            Some('r') => out.push('\r'),
+            Some('n') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    out
+}
+
+const IRC_URL: &str = "wss://irc-ws.chat.twitch.tv:443";
+const PING_INTERVAL: Duration = Duration::from_secs(30);
+/// How long we wait for the PONG answering our PING before considering the connection dead
+const PONG_TIMEOUT: Duration = Duration::from_secs(10);
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+/// Network errors are retried forever, at most this far apart
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Chat connection lifecycle, emitted as `chat-connection-state`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum ConnectionState {
+    Connecting,
+    Connected,
+    Reconnecting { attempt: u32, delay_ms: u64 },
+    /// Twitch rejected our credentials; only a new login or `join_chat` starts over
+    Failed { reason: String },
+}
+
+/// The single IRC connection of the current account. Dropping it closes the connection.
 pub struct ChatConnection {
-    pub sender: mpsc::Sender<String>,
+    pub sender: mpsc::Sender<ChatCommand>,
+    /// Account the connection is logged in as, `None` when anonymous
+    pub login: Option<String>,
+    /// Token the connection authenticates with, to notice a new login of the same account
+    access_token: Option<String>,
+    handle: tauri::async_runtime::JoinHandle<()>,
 }
 
-pub async fn connect_chat(
-    channel: String, 
+impl ChatConnection {
+    /// Whether this connection can still serve `login` with `access_token`. The task only ends
+    /// for good when Twitch rejects our credentials.
+    pub fn is_usable_for(&self, login: &Option<String>, access_token: &Option<String>) -> bool {
+        !self.sender.is_closed() && &self.login == login && &self.access_token == access_token
+    }
+
+    pub async fn join(&self, channel: &str) -> anyhow::Result<()> {
+        self.sender.send(ChatCommand::Join(channel.to_lowercase())).await
+            .map_err(|_| anyhow::anyhow!("Chat connection closed"))
+    }
+
+    pub async fn part(&self, channel: &str) -> anyhow::Result<()> {
+        self.sender.send(ChatCommand::Part(channel.to_lowercase())).await
+            .map_err(|_| anyhow::anyhow!("Chat connection closed"))
+    }
+
+    /// Queue a message, optionally replying to `reply_parent_id`, and return a receiver
+    /// resolved once Twitch accepts or refuses it
+    pub async fn send(
+        &self,
+        channel: &str,
+        text: String,
+        reply_parent_id: Option<String>,
+        nonce: String,
+    ) -> anyhow::Result<oneshot::Receiver<SendResult>> {
+        let (responder, result) = oneshot::channel();
+        let message = OutgoingMessage { nonce, channel: channel.to_lowercase(), text, reply_parent_id, responder };
+        self.sender.send(ChatCommand::Send(message)).await
+            .map_err(|_| anyhow::anyhow!("Chat connection closed"))?;
+        Ok(result)
+    }
+}
+
+impl Drop for ChatConnection {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+/// Everything a connection needs to (re)connect on its own
+struct ChatSession {
+    window: Window,
+    access_token: Option<String>,
+    username: Option<String>,
+    chat_state: SharedChatState,
+}
+
+/// Messages waiting for the rate limiter, kept across reconnects
+#[derive(Default)]
+struct Outbox {
+    queue: VecDeque<OutgoingMessage>,
+    limiter: RateLimiter,
+}
+
+/// Why a single websocket session ended
+enum SessionEnd {
+    /// The `ChatConnection` was dropped, stop for good
+    Closed,
+    /// Twitch asked us to reconnect (RECONNECT)
+    Reconnect,
+    /// Twitch rejected our credentials, retrying won't help
+    AuthFailed(String),
+    /// Network error, server close or missing PONG
+    Error(String),
+}
+
+/// Start a chat connection that reconnects by itself and rejoins `ChatState::joined`
+/// until its `ChatConnection` is dropped
+pub fn connect_chat(
     window: Window, 
     access_token: Option<String>,
     username: Option<String>,
-) -> anyhow::Result<ChatConnection> {
-    let url = "wss://irc-ws.chat.twitch.tv:443";
-    let (ws_stream, _) = connect_async(url).await?;
-    let (mut write, mut read) = ws_stream.split();
+    chat_state: SharedChatState,
+) -> ChatConnection {
+    // Create channel for joins, parts and messages
+    let (tx, rx) = mpsc::channel::<ChatCommand>(100);
+
+    let login = username.as_ref().filter(|_| access_token.is_some()).map(|u| u.to_lowercase());
+    let session = ChatSession { window, access_token: access_token.clone(), username, chat_state };
+    let handle = tauri::async_runtime::spawn(run_connection(session, rx));
+
+    ChatConnection { sender: tx, login, access_token, handle }
+}
 
-    // Create channel for sending messages
-    let (tx, mut rx) = mpsc::channel::<String>(100);
+async fn run_connection(session: ChatSession, mut rx: mpsc::Receiver<ChatCommand>) {
+    let mut attempt = 0;
+    let mut outbox = Outbox::default();
+    // A replaced connection may have been aborted while registered or with messages in flight,
+    // which our first USERSTATE would otherwise acknowledge
+    {
+        let mut chat_state = session.chat_state.lock().await;
+        chat_state.connected = false;
+        chat_state.fail_pending();
+    }
+    let _ = session.window.emit("chat-connection-state", ConnectionState::Connecting);
+
+    loop {
+        let end = run_session(&session, &mut rx, &mut outbox, &mut attempt).await;
+        {
+            let mut chat_state = session.chat_state.lock().await;
+            chat_state.connected = false;
+            // Messages in flight on the old connection will never be acknowledged
+            chat_state.fail_pending();
+        }
+
+        match end {
+            SessionEnd::Closed => break,
+            SessionEnd::AuthFailed(reason) => {
+                error!("[Chat] Authentication failed: {}", reason);
+                let _ = session.window.emit("chat-connection-state", ConnectionState::Failed { reason });
+                break;
+            }
+            SessionEnd::Reconnect => {
+                info!("[Chat] Server requested a reconnect");
+                let _ = session.window.emit("chat-connection-state", ConnectionState::Reconnecting { attempt: 0, delay_ms: 0 });
+            }
+            SessionEnd::Error(reason) => {
+                attempt += 1;
+                let delay = backoff_delay(attempt);
+                error!("[Chat] Connection lost ({}), reconnecting in {:?} (attempt {})", reason, delay, attempt);
+                let _ = session.window.emit("chat-connection-state", ConnectionState::Reconnecting {
+                    attempt,
+                    delay_ms: delay.as_millis() as u64,
+                });
+                tokio::time::sleep(delay).await;
+            }
+        }
+    }
+    info!("[Chat] Connection ended");
+}
+
+/// Exponential backoff with a little jitter so clients don't reconnect in lockstep
+pub fn backoff_delay(attempt: u32) -> Duration {
+    let exp = INITIAL_BACKOFF.saturating_mul(1 << attempt.saturating_sub(1).min(16));
+    exp.min(MAX_BACKOFF) + Duration::from_millis(rand::random_range(0..250))
+}
+
+async fn run_session(
+    session: &ChatSession,
+    rx: &mut mpsc::Receiver<ChatCommand>,
+    outbox: &mut Outbox,
+    attempt: &mut u32,
+) -> SessionEnd {
+    let (mut write, mut read) = match connect_async(IRC_URL).await {
+        Ok((ws_stream, _)) => ws_stream.split(),
+        Err(e) => return SessionEnd::Error(e.to_string()),
+    };
 
     // Send initial IRC commands
-    write.send(Message::Text("CAP REQ :twitch.tv/tags twitch.tv/commands".into())).await?;
+    let mut handshake = vec!["CAP REQ :twitch.tv/tags twitch.tv/commands".to_string()];
     
     // Use authenticated or anonymous connection
-    if let (Some(token), Some(user)) = (&access_token, &username) {
-        write.send(Message::Text(format!("PASS oauth:{}", token).into())).await?;
-        write.send(Message::Text(format!("NICK {}", user.to_lowercase()).into())).await?;
+    if let (Some(token), Some(user)) = (&session.access_token, &session.username) {
+        handshake.push(format!("PASS oauth:{}", token));
+        handshake.push(format!("NICK {}", user.to_lowercase()));
         info!("[Chat] Connecting as authenticated user: {}", user);
     } else {
-        write.send(Message::Text("PASS SCHMOOPIE".into())).await?;
-        write.send(Message::Text("NICK justinfan12345".into())).await?;
+        handshake.push("PASS SCHMOOPIE".to_string());
+        handshake.push("NICK justinfan12345".to_string());
         info!("[Chat] Connecting as anonymous user");
     }
-    
-    write.send(Message::Text(format!("JOIN #{}", channel).into())).await?;
 
-    let channel_clone = channel.clone();
-    let channel_for_read = channel.clone();
-    
-    // Spawn task to handle incoming messages
-    let window_clone = window.clone();
-    tokio::spawn(async move {
-        while let Some(msg) = read.next().await {
-            match msg {
-                Ok(msg) if msg.is_text() => {
-                    let text = msg.to_text().unwrap_or("");
-                    for line in text.lines() {
-                        if line.starts_with("PING") {
-                            // PING handled in write task
-                        } else if line.contains("PRIVMSG") {
-                            if let Some(mut parsed) = parse_irc_message(line) {
-                                // Include channel info so frontend can filter
-                                parsed.channel = channel_for_read.clone();
-                                let _ = window_clone.emit("chat-message", parsed);
+    // Rejoin every channel we were in before a reconnect
+    let joined: Vec<String> = session.chat_state.lock().await.joined.iter().cloned().collect();
+    for channel in joined {
+        handshake.push(format!("JOIN #{}", channel));
+    }
+
+    for line in handshake {
+        if let Err(e) = write.send(Message::Text(line.into())).await {
+            return SessionEnd::Error(e.to_string());
+        }
+    }
+
+    let mut ping_interval = tokio::time::interval(PING_INTERVAL);
+    let mut pong_deadline: Option<tokio::time::Instant> = None;
+    let mut ack_sweep = tokio::time::interval(Duration::from_secs(1));
+    // Messages queued before a reconnect go out as soon as the limiter allows
+    let mut next_flush = Some(tokio::time::Instant::now());
+
+    loop {
+        tokio::select! {
+            _ = ping_interval.tick() => {
+                if write.send(Message::Text("PING :tmi.twitch.tv".into())).await.is_err() {
+                    return SessionEnd::Error("Failed to send PING".to_string());
+                }
+                if pong_deadline.is_none() {
+                    pong_deadline = Some(tokio::time::Instant::now() + PONG_TIMEOUT);
+                }
+            }
+            _ = tokio::time::sleep_until(pong_deadline.unwrap_or_else(tokio::time::Instant::now)), if pong_deadline.is_some() => {
+                return SessionEnd::Error("PONG timeout".to_string());
+            }
+            msg = read.next() => {
+                match msg {
+                    Some(Ok(msg)) if msg.is_text() => {
+                        let text = msg.to_text().unwrap_or("");
+                        for line in text.lines() {
+                            let Some(irc) = parse_irc_line(line) else { continue };
+                            match irc.command {
+                                "PING" => {
+                                    let pong = format!("PONG :{}", irc.trailing.unwrap_or("tmi.twitch.tv"));
+                                    if write.send(Message::Text(pong.into())).await.is_err() {
+                                        return SessionEnd::Error("Failed to send PONG".to_string());
+                                    }
+                                }
+                                "PONG" => pong_deadline = None,
+                                "RECONNECT" => return SessionEnd::Reconnect,
+                                "001" => {
+                                    info!("[Chat] Connected");
+                                    *attempt = 0;
+                                    session.chat_state.lock().await.connected = true;
+                                    let _ = session.window.emit("chat-connection-state", ConnectionState::Connected);
+                                }
+                                _ => {
+                                    if let Some(end) = handle_line(session, &irc, line).await {
+                                        return end;
+                                    }
+                                }
                             }
-                        } else if line.contains("NOTICE") {
-                            // Handle notices (e.g., slow mode, sub only, etc.)
-                            info!("[Chat] Notice: {}", line);
-                            // Emit notice to frontend
-                            let _ = window_clone.emit("chat-notice", line.to_string());
-                        } else if line.contains("USERNOTICE") {
-                            // Handle user notices (subs, raids, etc.)
-                            info!("[Chat] UserNotice: {}", line);
                         }
                     }
+                    Some(Ok(Message::Close(frame))) => {
+                        return SessionEnd::Error(format!("Closed by server: {:?}", frame));
+                    }
+                    Some(Err(e)) => {
+                        error!("[Chat] Read error: {}", e);
+                        return SessionEnd::Error(e.to_string());
+                    }
+                    None => return SessionEnd::Error("Connection closed".to_string()),
+                    _ => {}
+                }
+            }
+            _ = ack_sweep.tick() => {
+                session.chat_state.lock().await.expire_pending();
+            }
+            _ = tokio::time::sleep_until(next_flush.unwrap_or_else(tokio::time::Instant::now)), if next_flush.is_some() => {
+                match flush_outbox(session, &mut write, outbox).await {
+                    Ok(next) => next_flush = next,
+                    Err(e) => {
+                        error!("[Chat] Failed to write to chat: {}", e);
+                        return SessionEnd::Error(e.to_string());
+                    }
                 }
-                Err(e) => {
-                    error!("[Chat] Read error: {}", e);
-                    // Emit disconnect event so frontend can reconnect
-                    let _ = window_clone.emit("chat-disconnected", channel_clone.clone());
-                    break;
+            }
+            command = rx.recv() => {
+                let Some(command) = command else {
+                    return SessionEnd::Closed;
+                };
+                if let Err(e) = handle_command(session, &mut write, outbox, command).await {
+                    error!("[Chat] Failed to write to chat: {}", e);
+                    return SessionEnd::Error(e.to_string());
                 }
-                _ => {}
-            }
-        }
-        info!("[Chat] Read loop ended for #{}", channel_clone);
-        // Emit disconnect event
-        let _ = window_clone.emit("chat-disconnected", channel_clone.clone());
-    });
-
-    // Spawn task to handle outgoing messages and pings
-    let channel_for_write = channel.clone();
-    tokio::spawn(async move {
-        let mut ping_interval = tokio::time::interval(std::time::Duration::from_secs(30));
-        
-        loop {
-            tokio::select! {
-                _ = ping_interval.tick() => {
-                    if write.send(Message::Text("PING :tmi.twitch.tv".into())).await.is_err() {
-                        break;
+                next_flush = Some(tokio::time::Instant::now());
+            }
+        }
+    }
+}
+
+type WsWrite = futures_util::stream::SplitSink<
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>,
+    Message,
+>;
+
+/// Apply a join, part or send request to the live connection
+async fn handle_command(session: &ChatSession, write: &mut WsWrite, outbox: &mut Outbox, command: ChatCommand) -> anyhow::Result<()> {
+    match command {
+        ChatCommand::Join(channel) => {
+            if session.chat_state.lock().await.joined.insert(channel.clone()) {
+                info!("[Chat] Joining #{}", channel);
+                write.send(Message::Text(format!("JOIN #{}", channel).into())).await?;
+            }
+        }
+        ChatCommand::Part(channel) => {
+            let was_joined = {
+                let mut state = session.chat_state.lock().await;
+                state.rooms.remove(&channel);
+                state.user_states.remove(&channel);
+                state.chatters.remove(&channel);
+                state.user_messages.remove(&channel);
+                state.joined.remove(&channel)
+            };
+            if was_joined {
+                info!("[Chat] Leaving #{}", channel);
+                write.send(Message::Text(format!("PART #{}", channel).into())).await?;
+            }
+        }
+        // Messages wait in the outbox until `flush_outbox` lets them through
+        ChatCommand::Send(message) => outbox.queue.push_back(message),
+    }
+    Ok(())
+}
+
+/// Write every queued message the rate limiter allows. Messages of the same channel share
+/// the same wait, so they keep their order. Returns when the next queued message becomes sendable.
+async fn flush_outbox(session: &ChatSession, write: &mut WsWrite, outbox: &mut Outbox) -> anyhow::Result<Option<tokio::time::Instant>> {
+    let mut waits: Vec<Duration> = Vec::new();
+    let mut index = 0;
+
+    while index < outbox.queue.len() {
+        let channel = outbox.queue[index].channel.clone();
+        let (privileged, slow) = {
+            let state = session.chat_state.lock().await;
+            (state.user_state(&channel).is_privileged(), state.rooms.get(&channel).map(|r| r.slow).unwrap_or(0))
+        };
+
+        let now = std::time::Instant::now();
+        let wait = outbox.limiter.wait(&channel, privileged, slow, now);
+        if !wait.is_zero() {
+            waits.push(wait);
+            index += 1;
+            continue;
+        }
+
+        let Some(OutgoingMessage { channel, text, reply_parent_id, responder, .. }) = outbox.queue.remove(index) else { break };
+        let wire_text = outbox.limiter.prepare(&channel, &text, now);
+        let irc_msg = match &reply_parent_id {
+            Some(parent_id) => format!("@reply-parent-msg-id={} PRIVMSG #{} :{}", parent_id, channel, wire_text),
+            None => format!("PRIVMSG #{} :{}", channel, wire_text),
+        };
+        info!("[Chat] Sending message: {}", irc_msg);
+        session.chat_state.lock().await.pending.push_back(PendingMessage {
+            channel: channel.clone(),
+            text,
+            reply_parent_id,
+            sent_at: now,
+            responder,
+        });
+        write.send(Message::Text(irc_msg.into())).await?;
+        outbox.limiter.record(&channel, wire_text, privileged, now);
+        info!("[Chat] Message sent successfully");
+
+        let mut state = session.chat_state.lock().await;
+        if let Some(room) = state.rooms.get_mut(&channel) {
+            room.last_sent_at = Some(chrono::Utc::now().timestamp_millis());
+            let _ = session.window.emit("chat-room-state", room.clone());
+        }
+    }
+
+    // Tell the UI where each held-back message stands
+    for (i, (message, wait)) in outbox.queue.iter().zip(&waits).enumerate() {
+        let _ = session.window.emit("chat-send-queued", QueuedMessageStatus {
+            nonce: message.nonce.clone(),
+            channel: message.channel.clone(),
+            position: i + 1,
+            wait_ms: wait.as_millis() as u64,
+        });
+    }
+
+    Ok(waits.iter().min().map(|wait| tokio::time::Instant::now() + *wait))
+}
+
+/// Update state and emit events for an incoming line. Returns `Some` when the session must end.
+async fn handle_line(session: &ChatSession, irc: &IrcLine<'_>, line: &str) -> Option<SessionEnd> {
+    let window = &session.window;
+    match irc.command {
+        "PRIVMSG" => {
+            if let Some(parsed) = parse_irc_message(irc) {
+                {
+                    let mut state = session.chat_state.lock().await;
+                    state.observe(&parsed);
+                    if parsed.reply.is_some() {
+                        state.threads.add(&parsed);
+                    }
+                    if let Some(log) = &state.chat_log {
+                        log.record(&parsed);
                     }
+                    state.history.push(parsed.clone());
                 }
-                msg = rx.recv() => {
-                    match msg {
-                        Some(text) => {
-                            let irc_msg = format!("PRIVMSG #{} :{}", channel_for_write, text);
-                            info!("[Chat] Sending message: {}", irc_msg);
-                            match write.send(Message::Text(irc_msg.into())).await {
-                                Ok(_) => info!("[Chat] Message sent successfully"),
-                                Err(e) => {
-                                    error!("[Chat] Failed to send message: {}", e);
-                                    break;
-                                }
-                            }
-                        }
-                        None => break,
+                let _ = window.emit("chat-message", parsed);
+            }
+        }
+        "ROOMSTATE" => {
+            let room = irc.channel?;
+            let room_state = {
+                let mut state = session.chat_state.lock().await;
+                let entry = state.rooms.entry(room.to_string()).or_insert_with(|| RoomState {
+                    channel: room.to_string(),
+                    ..Default::default()
+                });
+                entry.apply_tags(&irc.tags);
+                entry.clone()
+            };
+            info!("[Chat] Room state for #{}: {:?}", room, room_state);
+            let _ = window.emit("chat-room-state", room_state);
+        }
+        "GLOBALUSERSTATE" => {
+            let user_state = {
+                let mut state = session.chat_state.lock().await;
+                state.global_user_state.apply_tags(&irc.tags);
+                state.global_user_state.clone()
+            };
+            let _ = window.emit("chat-user-state", user_state);
+        }
+        "USERSTATE" => {
+            let room = irc.channel?;
+            let (user_state, echo) = {
+                let mut state = session.chat_state.lock().await;
+                let global = state.global_user_state.clone();
+                let entry = state.user_states.entry(room.to_string()).or_insert_with(|| UserState {
+                    channel: Some(room.to_string()),
+                    ..global
+                });
+                entry.apply_tags(&irc.tags);
+                let user_state = entry.clone();
+
+                // A USERSTATE acknowledging one of our PRIVMSGs carries the new message's id;
+                // the ones sent on JOIN don't
+                let ack_id = irc.tags.get("id").filter(|id| !id.is_empty()).cloned();
+                let echo = ack_id.and_then(|id| {
+                    let pending = state.take_pending(room)?;
+                    let _ = pending.responder.send(SendResult::Sent { message_id: id.clone() });
+                    Some(state.build_echo(room, id, pending.text, pending.reply_parent_id, session.username.as_deref()))
+                });
+                if let Some(echo) = &echo {
+                    state.observe(echo);
+                    state.threads.add(echo);
+                    if let Some(log) = &state.chat_log {
+                        log.record(echo);
                     }
+                    state.history.push(echo.clone());
                 }
+                (user_state, echo)
+            };
+            let _ = window.emit("chat-user-state", user_state);
+            if let Some(echo) = echo {
+                let _ = window.emit("chat-message", echo);
             }
         }
-        info!("[Chat] Write loop ended for #{}", channel_for_write);
-    });
+        "NOTICE" => {
+            // Handle notices (e.g., slow mode, sub only, etc.)
+            info!("[Chat] Notice: {}", line);
 
-    Ok(ChatConnection { sender: tx })
-}
+            // Login failures come as a channel-less NOTICE right after PASS/NICK
+            let text = irc.trailing.unwrap_or("");
+            if irc.channel.is_none() && (text.contains("Login authentication failed") || text.contains("Improperly formatted auth")) {
+                return Some(SessionEnd::AuthFailed(text.to_string()));
+            }
 
-fn parse_irc_message(text: &str) -> Option<ChatMessage> {
-    let parts: Vec<&str> = text.splitn(2, " PRIVMSG #").collect();
-    if parts.len() < 2 { return None; }
+            let failure = irc.tags.get("msg-id").and_then(|id| SendFailure::from_msg_id(id));
+            if let (Some(reason), Some(room)) = (failure, irc.channel) {
+                let pending = session.chat_state.lock().await.take_pending(room);
+                if let Some(pending) = pending {
+                    let _ = pending.responder.send(SendResult::failed(reason, text));
+                }
+            }
+            // Emit notice to frontend
+            let _ = window.emit("chat-notice", ChatNotice {
+                channel: irc.channel.map(|c| c.to_string()),
+                msg_id: irc.tags.get("msg-id").cloned(),
+                message: text.to_string(),
+            });
+        }
+        "USERNOTICE" => {
+            // Handle user notices (subs, raids, etc.)
+            info!("[Chat] UserNotice: {}", line);
+        }
+        _ => {}
+    }
+    None
+}
 
-    let tags_part = parts[0];
-    let content_parts: Vec<&str> = parts[1].splitn(2, " :").collect();
-    if content_parts.len() < 2 { return None; }
+/// Strip the CTCP ACTION wrapper used by `/me` messages
+fn split_action(text: &str) -> (String, bool) {
+    match text.strip_prefix("\u{1}ACTION ") {
+        Some(action) => (action.trim_end_matches('\u{1}').to_string(), true),
+        None => (text.to_string(), false),
+    }
+}
 
-    let message = content_parts[1].trim();
+fn parse_irc_message(irc: &IrcLine) -> Option<ChatMessage> {
+    let (message, is_action) = split_action(irc.trailing?.trim());
     
     // Extract message ID for deduplication
-    let id = tags_part.split(';')
-        .find(|s| s.starts_with("id="))
-        .and_then(|s| s.split('=').nth(1))
-        .unwrap_or("")
-        .to_string();
+    let id = irc.tags.get("id").cloned().unwrap_or_default();
     
-    let user = tags_part.split(';')
-        .find(|s| s.starts_with("display-name="))
-        .and_then(|s| s.split('=').nth(1))
+    let user = irc.tags.get("display-name")
+        .filter(|s| !s.is_empty())
+        .map(|s| s.as_str())
         .unwrap_or("Unknown");
 
-    let color = tags_part.split(';')
-        .find(|s| s.starts_with("color="))
-        .and_then(|s| s.split('=').nth(1))
+    let color = irc.tags.get("color")
         .filter(|s| !s.is_empty())
-        .map(|s| s.to_string());
+        .cloned();
 
-    let badges_str = tags_part.split(';')
-        .find(|s| s.starts_with("badges="))
-        .and_then(|s| s.split('=').nth(1))
-        .unwrap_or("");
+    let badges = parse_badges(irc.tags.get("badges").map(|s| s.as_str()).unwrap_or(""));
 
+    Some(ChatMessage {
+        id,
+        user: user.to_string(),
+        login: irc.nick.map(|n| n.to_string()).unwrap_or_else(|| user.to_lowercase()),
+        user_id: irc.tags.get("user-id").cloned().unwrap_or_default(),
+        message,
+        is_action,
+        color,
+        badges,
+        badge_info: parse_badges(irc.tags.get("badge-info").map(|s| s.as_str()).unwrap_or("")),
+        // Include channel info so frontend can filter
+        channel: irc.channel?.to_string(),
+        timestamp: irc.tags.get("tmi-sent-ts").and_then(|ts| ts.parse().ok())
+            .unwrap_or_else(|| chrono::Utc::now().timestamp_millis()),
+        reply: ReplyInfo::from_tags(&irc.tags),
+        historical: irc.tags.get("historical").is_some_and(|h| h == "1"),
+        tags: irc.tags.clone(),
+    })
+}
+
+/// Parse a raw PRIVMSG line received outside the connection, e.g. from the recent-messages
+/// backfill. Other commands are ignored.
+pub fn parse_privmsg_line(line: &str) -> Option<ChatMessage> {
+    let irc = parse_irc_line(line)?;
+    if irc.command != "PRIVMSG" {
+        return None;
+    }
+    parse_irc_message(&irc)
+}
+
+fn parse_badges(badges_str: &str) -> Vec<(String, String)> {
     let mut badges = Vec::new();
     for b in badges_str.split(',') {
         let pair: Vec<&str> = b.split('/').collect();
@@ -169,13 +1128,5 @@ fn parse_irc_message(text: &str) -> Option<ChatMessage> {
             badges.push((pair[0].to_string(), pair[1].to_string()));
         }
     }
-
-    Some(ChatMessage {
-        id,
-        user: user.to_string(),
-        message: message.to_string(),
-        color,
-        badges,
-        channel: String::new(), // Will be set by caller
-    })
+    badges
 }
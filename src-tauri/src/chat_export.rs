@@ -0,0 +1,92 @@
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::chat::ChatMessage;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    /// One `[HH:MM:SS] login: message` line per message
+    Text,
+    /// One JSON `ChatMessage` per line, raw IRC tags included
+    JsonLines,
+    /// Chatterino's layout: `Twitch/Channels/<channel>/<channel>-YYYY-MM-DD.log` under the target directory
+    Chatterino,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExportSummary {
+    pub message_count: usize,
+    /// Files written, a single one except for the Chatterino layout
+    pub files: Vec<String>,
+}
+
+/// Write `messages` (oldest first) of `channel` to `path`, a file or for Chatterino a directory
+pub fn export(channel: &str, messages: &[ChatMessage], format: ExportFormat, path: &Path) -> Result<ExportSummary> {
+    let files = match format {
+        ExportFormat::Text => {
+            let mut out = create(path)?;
+            for message in messages {
+                writeln!(out, "{}", text_line(message))?;
+            }
+            out.flush()?;
+            vec![path.to_path_buf()]
+        }
+        ExportFormat::JsonLines => {
+            let mut out = create(path)?;
+            for message in messages {
+                serde_json::to_writer(&mut out, message)?;
+                writeln!(out)?;
+            }
+            out.flush()?;
+            vec![path.to_path_buf()]
+        }
+        ExportFormat::Chatterino => export_chatterino(channel, messages, path)?,
+    };
+    Ok(ExportSummary {
+        message_count: messages.len(),
+        files: files.iter().map(|f| f.display().to_string()).collect(),
+    })
+}
+
+/// One log file per local day, each framed by Chatterino's start and stop markers
+fn export_chatterino(channel: &str, messages: &[ChatMessage], root: &Path) -> Result<Vec<PathBuf>> {
+    let dir = root.join("Twitch").join("Channels").join(channel);
+    std::fs::create_dir_all(&dir)?;
+
+    let mut files = Vec::new();
+    for day in messages.chunk_by(|a, b| local_time(a.timestamp).date_naive() == local_time(b.timestamp).date_naive()) {
+        let (first, last) = (&day[0], &day[day.len() - 1]);
+        let path = dir.join(format!("{}-{}.log", channel, local_time(first.timestamp).format("%Y-%m-%d")));
+        let mut out = create(&path)?;
+        writeln!(out, "# Start logging at {}", local_time(first.timestamp).format("%Y-%m-%d %H:%M:%S %Z"))?;
+        for message in day {
+            writeln!(out, "{}", text_line(message))?;
+        }
+        writeln!(out, "# Stop logging at {}", local_time(last.timestamp).format("%Y-%m-%d %H:%M:%S %Z"))?;
+        out.flush()?;
+        files.push(path);
+    }
+    Ok(files)
+}
+
+fn create(path: &Path) -> Result<BufWriter<File>> {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    Ok(BufWriter::new(File::create(path)?))
+}
+
+fn local_time(timestamp: i64) -> DateTime<Local> {
+    DateTime::from_timestamp_millis(timestamp).unwrap_or_default().with_timezone(&Local)
+}
+
+/// `[HH:MM:SS] login: message`, local time, as Chatterino writes it
+fn text_line(message: &ChatMessage) -> String {
+    let author = if message.login.is_empty() { &message.user } else { &message.login };
+    format!("[{}] {}: {}", local_time(message.timestamp).format("%H:%M:%S"), author, message.message)
+}
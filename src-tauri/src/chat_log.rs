@@ -0,0 +1,285 @@
+use anyhow::Result;
+use log::error;
+use rusqlite::{params, params_from_iter, Connection};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
+
+use crate::chat::ChatMessage;
+
+/// Results returned by a search when no limit is given
+const DEFAULT_SEARCH_LIMIT: u32 = 100;
+const MAX_SEARCH_LIMIT: u32 = 1000;
+const DAY_MS: i64 = 86_400_000;
+/// Messages written per transaction at most
+const MAX_WRITE_BATCH: usize = 500;
+/// How long a statement waits on the other connection's lock
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS messages (
+        rowid INTEGER PRIMARY KEY,
+        msg_id TEXT UNIQUE,
+        channel TEXT NOT NULL,
+        day TEXT NOT NULL,
+        timestamp INTEGER NOT NULL,
+        user_id TEXT NOT NULL,
+        login TEXT NOT NULL,
+        message TEXT NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS messages_channel_day ON messages (channel, day);
+    CREATE INDEX IF NOT EXISTS messages_channel_timestamp ON messages (channel, timestamp);
+    CREATE INDEX IF NOT EXISTS messages_login ON messages (login, timestamp);
+    CREATE VIRTUAL TABLE IF NOT EXISTS messages_fts USING fts5 (message, content = 'messages', content_rowid = 'rowid');
+    CREATE TRIGGER IF NOT EXISTS messages_insert AFTER INSERT ON messages BEGIN
+        INSERT INTO messages_fts (rowid, message) VALUES (new.rowid, new.message);
+    END;
+    CREATE TRIGGER IF NOT EXISTS messages_delete AFTER DELETE ON messages BEGIN
+        INSERT INTO messages_fts (messages_fts, rowid, message) VALUES ('delete', old.rowid, old.message);
+    END;
+";
+
+/// Chat logging settings, stored as `chat_logs` in the settings store
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChatLogSettings {
+    /// Record messages of the channels we join. Off by default, like the recent-messages backfill.
+    pub enabled: bool,
+    /// Days logs are kept, `None` to keep them forever
+    pub retention_days: Option<u32>,
+}
+
+impl Default for ChatLogSettings {
+    fn default() -> Self {
+        Self { enabled: false, retention_days: Some(30) }
+    }
+}
+
+/// Filters of `search_chat_logs`, all optional
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ChatLogQuery {
+    /// Words to look for, matched as a phrase
+    pub text: Option<String>,
+    /// Author login
+    pub user: Option<String>,
+    pub channel: Option<String>,
+    /// Unix time in milliseconds, inclusive
+    pub from: Option<i64>,
+    /// Unix time in milliseconds, exclusive
+    pub to: Option<i64>,
+    pub limit: Option<u32>,
+}
+
+/// A channel and local day we have logs for
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChatLogDay {
+    pub channel: String,
+    /// `YYYY-MM-DD`, local time
+    pub day: String,
+    pub message_count: u32,
+}
+
+/// Work for the writer thread
+enum Write {
+    Message(Box<ChatMessage>),
+    /// Delete messages older than the cutoff (ms) and report how many went
+    Prune { cutoff: i64, done: mpsc::Sender<Result<usize>> },
+}
+
+/// Chat messages stored in SQLite, indexed by channel and local day. Cheap to clone.
+///
+/// Writes go through a dedicated thread with its own connection, so recording never waits on
+/// SQLite; reads share a second connection, which WAL lets run alongside the writer.
+#[derive(Clone)]
+pub struct ChatLog {
+    conn: Arc<Mutex<Connection>>,
+    writer: mpsc::Sender<Write>,
+}
+
+impl ChatLog {
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let write_conn = open_connection(path)?;
+        write_conn.execute_batch(SCHEMA)?;
+        // Safe with WAL: a crash may lose the last transactions but never corrupts the log
+        write_conn.pragma_update(None, "synchronous", "NORMAL")?;
+        let conn = open_connection(path)?;
+
+        let (writer, rx) = mpsc::channel();
+        std::thread::Builder::new()
+            .name("chat-log-writer".to_string())
+            .spawn(move || run_writer(write_conn, rx))?;
+        Ok(Self { conn: Arc::new(Mutex::new(conn)), writer })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Connection> {
+        // A panic mid-statement leaves nothing half-written in SQLite, so keep going
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Queue a message for the writer thread, which ignores ones already logged. Never blocks.
+    pub fn record(&self, message: &ChatMessage) {
+        if self.writer.send(Write::Message(Box::new(message.clone()))).is_err() {
+            error!("[ChatLog] Writer stopped, dropping message in #{}", message.channel);
+        }
+    }
+
+    /// Delete messages older than `retention_days` before `now` (ms). Returns how many were removed.
+    /// Blocks until the writer thread is done.
+    pub fn prune(&self, retention_days: u32, now: i64) -> Result<usize> {
+        let cutoff = now - i64::from(retention_days) * DAY_MS;
+        let (done, result) = mpsc::channel();
+        self.writer.send(Write::Prune { cutoff, done })
+            .map_err(|_| anyhow::anyhow!("Chat log writer stopped"))?;
+        result.recv().map_err(|_| anyhow::anyhow!("Chat log writer stopped"))?
+    }
+
+    /// Messages matching every filter of `query`, newest first
+    pub fn search(&self, query: &ChatLogQuery) -> Result<Vec<ChatMessage>> {
+        let mut conditions = Vec::new();
+        let mut values: Vec<rusqlite::types::Value> = Vec::new();
+        if let Some(text) = query.text.as_deref().map(str::trim).filter(|t| !t.is_empty()) {
+            conditions.push("rowid IN (SELECT rowid FROM messages_fts WHERE messages_fts MATCH ?)");
+            // Quote as a phrase so user input never parses as FTS syntax
+            values.push(format!("\"{}\"", text.replace('"', "\"\"")).into());
+        }
+        if let Some(user) = query.user.as_deref().filter(|u| !u.is_empty()) {
+            conditions.push("login = ?");
+            values.push(user.trim_start_matches('@').to_lowercase().into());
+        }
+        if let Some(channel) = query.channel.as_deref().filter(|c| !c.is_empty()) {
+            conditions.push("channel = ?");
+            values.push(channel.trim_start_matches('#').to_lowercase().into());
+        }
+        if let Some(from) = query.from {
+            conditions.push("timestamp >= ?");
+            values.push(from.into());
+        }
+        if let Some(to) = query.to {
+            conditions.push("timestamp < ?");
+            values.push(to.into());
+        }
+        let filter = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+        let limit = query.limit.unwrap_or(DEFAULT_SEARCH_LIMIT).min(MAX_SEARCH_LIMIT);
+        let sql = format!("SELECT data FROM messages {} ORDER BY timestamp DESC LIMIT {}", filter, limit);
+        self.query_messages(&sql, values)
+    }
+
+    /// Every message of `channel` in `[from, to)`, oldest first
+    pub fn range(&self, channel: &str, from: i64, to: i64) -> Result<Vec<ChatMessage>> {
+        self.query_messages(
+            "SELECT data FROM messages WHERE channel = ? AND timestamp >= ? AND timestamp < ? ORDER BY timestamp",
+            vec![channel.trim_start_matches('#').to_lowercase().into(), from.into(), to.into()],
+        )
+    }
+
+    fn query_messages(&self, sql: &str, values: Vec<rusqlite::types::Value>) -> Result<Vec<ChatMessage>> {
+        let conn = self.lock();
+        let mut statement = conn.prepare(sql)?;
+        let rows = statement.query_map(params_from_iter(values), |row| row.get::<_, String>(0))?;
+        let mut messages = Vec::new();
+        for data in rows {
+            // Skip rows written by an incompatible version rather than failing the whole search
+            if let Ok(message) = serde_json::from_str(&data?) {
+                messages.push(message);
+            }
+        }
+        Ok(messages)
+    }
+
+    /// Days with logs, most recent first, optionally for one channel
+    pub fn days(&self, channel: Option<&str>) -> Result<Vec<ChatLogDay>> {
+        let conn = self.lock();
+        let mut statement = conn.prepare(
+            "SELECT channel, day, COUNT(*) FROM messages WHERE ?1 IS NULL OR channel = ?1
+             GROUP BY channel, day ORDER BY day DESC, channel",
+        )?;
+        let rows = statement.query_map(params![channel.map(|c| c.to_lowercase())], |row| {
+            Ok(ChatLogDay { channel: row.get(0)?, day: row.get(1)?, message_count: row.get(2)? })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+}
+
+fn open_connection(path: &Path) -> Result<Connection> {
+    let conn = Connection::open(path)?;
+    conn.pragma_update(None, "journal_mode", "WAL")?;
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    Ok(conn)
+}
+
+/// Write queued messages in batches, one transaction each, until every `ChatLog` is dropped
+fn run_writer(mut conn: Connection, rx: mpsc::Receiver<Write>) {
+    while let Ok(first) = rx.recv() {
+        let mut messages = Vec::new();
+        let mut next = Some(first);
+        while let Some(write) = next.take() {
+            match write {
+                Write::Message(message) => messages.push(message),
+                Write::Prune { cutoff, done } => {
+                    // Keep the order: flush what was queued before pruning
+                    insert_batch(&mut conn, &messages);
+                    messages.clear();
+                    let removed = conn.execute("DELETE FROM messages WHERE timestamp < ?1", params![cutoff])
+                        .map_err(anyhow::Error::from);
+                    let _ = done.send(removed);
+                }
+            }
+            if messages.len() < MAX_WRITE_BATCH {
+                next = rx.try_recv().ok();
+            }
+        }
+        insert_batch(&mut conn, &messages);
+    }
+}
+
+fn insert_batch(conn: &mut Connection, messages: &[Box<ChatMessage>]) {
+    if messages.is_empty() {
+        return;
+    }
+    if let Err(e) = try_insert_batch(conn, messages) {
+        error!("[ChatLog] Failed to log {} messages: {}", messages.len(), e);
+    }
+}
+
+fn try_insert_batch(conn: &mut Connection, messages: &[Box<ChatMessage>]) -> Result<()> {
+    let transaction = conn.transaction()?;
+    {
+        let mut statement = transaction.prepare_cached(
+            "INSERT OR IGNORE INTO messages (msg_id, channel, day, timestamp, user_id, login, message, data)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        )?;
+        for message in messages {
+            let msg_id = Some(message.id.as_str()).filter(|id| !id.is_empty());
+            statement.execute(params![
+                msg_id,
+                message.channel,
+                local_day(message.timestamp),
+                message.timestamp,
+                message.user_id,
+                message.login,
+                message.message,
+                serde_json::to_string(message)?,
+            ])?;
+        }
+    }
+    transaction.commit()?;
+    Ok(())
+}
+
+/// Local `YYYY-MM-DD` of a Unix time in milliseconds
+pub fn local_day(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(timestamp)
+        .unwrap_or_default()
+        .with_timezone(&chrono::Local)
+        .format("%Y-%m-%d")
+        .to_string()
+}
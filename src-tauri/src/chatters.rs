@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// How a chatter is grouped in the chatters list
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChatterRole {
+    Broadcaster,
+    Moderator,
+    Vip,
+    Viewer,
+}
+
+impl ChatterRole {
+    pub fn from_badges(badges: &[(String, String)]) -> Self {
+        let has = |set: &str| badges.iter().any(|(name, _)| name == set);
+        if has("broadcaster") {
+            ChatterRole::Broadcaster
+        } else if has("moderator") {
+            ChatterRole::Moderator
+        } else if has("vip") {
+            ChatterRole::Vip
+        } else {
+            ChatterRole::Viewer
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Chatter {
+    pub user_id: String,
+    pub login: String,
+    pub display_name: String,
+    pub role: ChatterRole,
+}
+
+/// Users in a channel, grouped by role
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ChatterList {
+    pub broadcaster: Vec<Chatter>,
+    pub moderators: Vec<Chatter>,
+    pub vips: Vec<Chatter>,
+    pub viewers: Vec<Chatter>,
+    /// Everyone in the channel, across all pages
+    pub total: u32,
+    /// Cursor of the next Helix page, `None` on the last one
+    pub cursor: Option<String>,
+    /// `false` when we don't moderate the channel and only know who talked in chat
+    pub from_helix: bool,
+}
+
+impl ChatterList {
+    /// List made only of the users we saw talking
+    pub fn from_observed(observed: impl IntoIterator<Item = Chatter>) -> Self {
+        let mut list = ChatterList::default();
+        for chatter in observed {
+            list.push(chatter);
+        }
+        list.total = list.len() as u32;
+        list.sort();
+        list
+    }
+
+    /// List from a Helix `/chat/chatters` page. Helix has no roles, so they come from the
+    /// badges of users seen in chat; anyone else is a viewer.
+    pub fn from_helix(page: &serde_json::Value, observed: &HashMap<String, Chatter>, channel: &str) -> Self {
+        let mut list = ChatterList {
+            total: page.get("total").and_then(|t| t.as_u64()).unwrap_or(0) as u32,
+            cursor: page.get("pagination").and_then(|p| p.get("cursor")).and_then(|c| c.as_str())
+                .filter(|c| !c.is_empty())
+                .map(|c| c.to_string()),
+            from_helix: true,
+            ..Default::default()
+        };
+
+        let entries = page.get("data").and_then(|d| d.as_array()).cloned().unwrap_or_default();
+        for entry in entries {
+            let field = |name: &str| entry.get(name).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+            let login = field("user_login");
+            let role = match observed.get(&login) {
+                _ if login == channel => ChatterRole::Broadcaster,
+                Some(seen) => seen.role,
+                None => ChatterRole::Viewer,
+            };
+            list.push(Chatter {
+                user_id: field("user_id"),
+                display_name: field("user_name"),
+                login,
+                role,
+            });
+        }
+        list.sort();
+        list
+    }
+
+    fn push(&mut self, chatter: Chatter) {
+        match chatter.role {
+            ChatterRole::Broadcaster => self.broadcaster.push(chatter),
+            ChatterRole::Moderator => self.moderators.push(chatter),
+            ChatterRole::Vip => self.vips.push(chatter),
+            ChatterRole::Viewer => self.viewers.push(chatter),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.broadcaster.len() + self.moderators.len() + self.vips.len() + self.viewers.len()
+    }
+
+    fn sort(&mut self) {
+        for group in [&mut self.broadcaster, &mut self.moderators, &mut self.vips, &mut self.viewers] {
+            group.sort_by(|a, b| a.login.cmp(&b.login));
+        }
+    }
+}
@@ -0,0 +1,326 @@
+use futures_util::{SinkExt, StreamExt};
+use log::{info, error};
+use reqwest::StatusCode;
+use serde_json::Value;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
+
+use crate::chat::backoff_delay;
+use crate::twitch::{HelixError, TwitchClient};
+
+const EVENTSUB_URL: &str = "wss://eventsub.wss.twitch.tv/ws";
+/// Used until the welcome message tells us the real keepalive interval
+const DEFAULT_KEEPALIVE: Duration = Duration::from_secs(10);
+/// Slack on top of the keepalive interval before we consider the connection dead
+const KEEPALIVE_GRACE: Duration = Duration::from_secs(5);
+/// How often subscriptions that failed to be created are tried again
+const RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// An EventSub subscription type with its condition, e.g. `user.whisper.message` v1 for a user id
+#[derive(Debug, Clone, PartialEq)]
+pub struct Subscription {
+    pub kind: String,
+    pub version: String,
+    pub condition: Value,
+}
+
+impl Subscription {
+    pub fn new(kind: &str, version: &str, condition: Value) -> Self {
+        Self { kind: kind.to_string(), version: version.to_string(), condition }
+    }
+}
+
+/// An event delivered for one of our subscriptions
+#[derive(Debug, Clone)]
+pub struct Notification {
+    /// Subscription type, e.g. `user.whisper.message`
+    pub kind: String,
+    pub event: Value,
+}
+
+/// Changes to the subscriptions of a running connection
+enum Command {
+    Subscribe(Subscription),
+    Unsubscribe(Subscription),
+}
+
+/// The EventSub WebSocket of the current account. Dropping it closes the connection.
+pub struct EventSubConnection {
+    /// Account the subscriptions belong to
+    pub user_id: String,
+    sender: mpsc::Sender<Command>,
+    handle: tauri::async_runtime::JoinHandle<()>,
+}
+
+impl EventSubConnection {
+    /// Subscribe on the current session and every session after a reconnect.
+    /// Subscriptions Twitch refuses are logged and dropped; other failures are retried.
+    pub async fn subscribe(&self, subscription: Subscription) -> anyhow::Result<()> {
+        self.send(Command::Subscribe(subscription)).await
+    }
+
+    /// Stop receiving a subscription's events, if we had it
+    pub async fn unsubscribe(&self, subscription: Subscription) -> anyhow::Result<()> {
+        self.send(Command::Unsubscribe(subscription)).await
+    }
+
+    async fn send(&self, command: Command) -> anyhow::Result<()> {
+        self.sender.send(command).await
+            .map_err(|_| anyhow::anyhow!("EventSub connection closed"))
+    }
+}
+
+impl Drop for EventSubConnection {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+/// Connect to EventSub as `user_id` and keep `subscriptions` active, sending their events to
+/// `notifications`. Network errors are retried with backoff until the connection is dropped.
+pub fn connect_eventsub(
+    client: TwitchClient,
+    user_id: &str,
+    subscriptions: Vec<Subscription>,
+    notifications: mpsc::UnboundedSender<Notification>,
+) -> EventSubConnection {
+    let (tx, rx) = mpsc::channel(32);
+    let handle = tauri::async_runtime::spawn(run_connection(client, subscriptions, rx, notifications));
+    EventSubConnection { user_id: user_id.to_string(), sender: tx, handle }
+}
+
+/// Why a session ended
+enum SessionEnd {
+    /// The connection handle was dropped
+    Closed,
+    /// Twitch moved us to another URL; subscriptions carry over
+    Reconnect(String),
+    Error(String),
+}
+
+/// Outcome of creating a subscription on a session
+enum Created {
+    Id(String),
+    /// Twitch refused it (invalid, not allowed or a duplicate), retrying won't help
+    Refused,
+    /// Network error, rate limit or server error, worth retrying
+    Failed,
+}
+
+/// A subscription we want, with its id once Twitch created it on the current session
+struct ActiveSubscription {
+    subscription: Subscription,
+    id: Option<String>,
+}
+
+/// Everything a session needs, kept across reconnects
+struct EventSubSession {
+    client: TwitchClient,
+    subscriptions: Vec<ActiveSubscription>,
+    notifications: mpsc::UnboundedSender<Notification>,
+}
+
+async fn run_connection(
+    client: TwitchClient,
+    subscriptions: Vec<Subscription>,
+    mut rx: mpsc::Receiver<Command>,
+    notifications: mpsc::UnboundedSender<Notification>,
+) {
+    let subscriptions = subscriptions.into_iter()
+        .map(|subscription| ActiveSubscription { subscription, id: None })
+        .collect();
+    let mut session = EventSubSession { client, subscriptions, notifications };
+    let mut url = EVENTSUB_URL.to_string();
+    let mut resubscribe = true;
+    let mut attempt = 0;
+
+    loop {
+        match run_session(&mut session, &url, resubscribe, &mut rx, &mut attempt).await {
+            SessionEnd::Closed => break,
+            SessionEnd::Reconnect(reconnect_url) => {
+                info!("[EventSub] Server requested a reconnect");
+                url = reconnect_url;
+                resubscribe = false;
+            }
+            SessionEnd::Error(reason) => {
+                attempt += 1;
+                let delay = backoff_delay(attempt);
+                error!("[EventSub] Connection lost ({}), reconnecting in {:?} (attempt {})", reason, delay, attempt);
+                tokio::time::sleep(delay).await;
+                // Subscriptions die with the session, so start over on a fresh one
+                url = EVENTSUB_URL.to_string();
+                resubscribe = true;
+            }
+        }
+    }
+    info!("[EventSub] Connection ended");
+}
+
+async fn run_session(
+    session: &mut EventSubSession,
+    url: &str,
+    resubscribe: bool,
+    rx: &mut mpsc::Receiver<Command>,
+    attempt: &mut u32,
+) -> SessionEnd {
+    let (ws_stream, _) = match connect_async(url).await {
+        Ok(stream) => stream,
+        Err(e) => return SessionEnd::Error(format!("Failed to connect: {}", e)),
+    };
+    let (mut write, mut read) = ws_stream.split();
+
+    let mut session_id: Option<String> = None;
+    let mut keepalive = DEFAULT_KEEPALIVE;
+    let mut deadline = Instant::now() + keepalive + KEEPALIVE_GRACE;
+    let mut retry = tokio::time::interval_at(Instant::now() + RETRY_INTERVAL, RETRY_INTERVAL);
+    retry.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep_until(deadline) => {
+                return SessionEnd::Error("Keepalive timeout".to_string());
+            }
+            _ = retry.tick() => {
+                if let Some(session_id) = &session_id {
+                    create_missing(session, session_id).await;
+                }
+            }
+            msg = read.next() => {
+                match msg {
+                    Some(Ok(Message::Text(text))) => {
+                        // Any message counts as a keepalive
+                        deadline = Instant::now() + keepalive + KEEPALIVE_GRACE;
+                        let Ok(message) = serde_json::from_str::<Value>(&text) else { continue };
+                        let message_type = message["metadata"]["message_type"].as_str().unwrap_or_default();
+                        let payload = &message["payload"];
+                        match message_type {
+                            "session_welcome" => {
+                                let id = payload["session"]["id"].as_str().unwrap_or_default().to_string();
+                                if let Some(secs) = payload["session"]["keepalive_timeout_seconds"].as_u64() {
+                                    keepalive = Duration::from_secs(secs);
+                                    deadline = Instant::now() + keepalive + KEEPALIVE_GRACE;
+                                }
+                                info!("[EventSub] Connected, session {}", id);
+                                *attempt = 0;
+                                // Subscriptions carry over to a session Twitch moved us to, not to a fresh one
+                                if resubscribe {
+                                    for active in &mut session.subscriptions {
+                                        active.id = None;
+                                    }
+                                }
+                                create_missing(session, &id).await;
+                                session_id = Some(id);
+                            }
+                            "session_keepalive" => {}
+                            "notification" => {
+                                let kind = payload["subscription"]["type"].as_str().unwrap_or_default().to_string();
+                                let _ = session.notifications.send(Notification { kind, event: payload["event"].clone() });
+                            }
+                            "session_reconnect" => {
+                                if let Some(reconnect_url) = payload["session"]["reconnect_url"].as_str() {
+                                    return SessionEnd::Reconnect(reconnect_url.to_string());
+                                }
+                            }
+                            "revocation" => {
+                                let kind = payload["subscription"]["type"].as_str().unwrap_or_default();
+                                let status = payload["subscription"]["status"].as_str().unwrap_or_default();
+                                error!("[EventSub] Subscription {} revoked: {}", kind, status);
+                                session.subscriptions.retain(|s| s.subscription.kind != kind);
+                            }
+                            other => info!("[EventSub] Unhandled message type: {}", other),
+                        }
+                    }
+                    Some(Ok(Message::Ping(data))) => {
+                        let _ = write.send(Message::Pong(data)).await;
+                    }
+                    Some(Ok(Message::Close(frame))) => {
+                        return SessionEnd::Error(format!("Closed by server: {:?}", frame));
+                    }
+                    Some(Ok(_)) => {}
+                    Some(Err(e)) => return SessionEnd::Error(format!("Read error: {}", e)),
+                    None => return SessionEnd::Error("Stream ended".to_string()),
+                }
+            }
+            command = rx.recv() => {
+                match command {
+                    None => {
+                        let _ = write.send(Message::Close(None)).await;
+                        return SessionEnd::Closed;
+                    }
+                    Some(Command::Subscribe(subscription)) => {
+                        // Twitch refuses duplicate subscriptions on a session
+                        if session.subscriptions.iter().any(|s| s.subscription == subscription) {
+                            continue;
+                        }
+                        // Before the welcome it is created with the others once the session starts
+                        let id = match &session_id {
+                            Some(session_id) => match create_subscription(&session.client, session_id, &subscription).await {
+                                Created::Id(id) => Some(id),
+                                Created::Refused => continue,
+                                Created::Failed => None,
+                            },
+                            None => None,
+                        };
+                        session.subscriptions.push(ActiveSubscription { subscription, id });
+                    }
+                    Some(Command::Unsubscribe(subscription)) => {
+                        let Some(index) = session.subscriptions.iter().position(|s| s.subscription == subscription) else {
+                            continue;
+                        };
+                        let removed = session.subscriptions.remove(index);
+                        if let Some(id) = removed.id {
+                            match session.client.delete_eventsub_subscription(&id).await {
+                                Ok(()) => info!("[EventSub] Unsubscribed from {}", subscription.kind),
+                                Err(e) => error!("[EventSub] Failed to unsubscribe from {}: {}", subscription.kind, e),
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Create the subscriptions not yet created on the session, dropping the ones Twitch refuses.
+/// The others stay without an id for the next retry or session.
+async fn create_missing(session: &mut EventSubSession, session_id: &str) {
+    let mut index = 0;
+    while index < session.subscriptions.len() {
+        let active = &mut session.subscriptions[index];
+        if active.id.is_none() {
+            match create_subscription(&session.client, session_id, &active.subscription).await {
+                Created::Id(id) => active.id = Some(id),
+                Created::Refused => {
+                    session.subscriptions.remove(index);
+                    continue;
+                }
+                Created::Failed => {}
+            }
+        }
+        index += 1;
+    }
+}
+
+/// Create a subscription on the session, logging failures
+async fn create_subscription(client: &TwitchClient, session_id: &str, subscription: &Subscription) -> Created {
+    match client.create_eventsub_subscription(&subscription.kind, &subscription.version, &subscription.condition, session_id).await {
+        Ok(id) => {
+            info!("[EventSub] Subscribed to {}", subscription.kind);
+            Created::Id(id)
+        }
+        Err(e) => {
+            let refused = e.downcast_ref::<HelixError>().is_some_and(|e| {
+                matches!(e.status, StatusCode::BAD_REQUEST | StatusCode::FORBIDDEN | StatusCode::CONFLICT)
+            });
+            if refused {
+                error!("[EventSub] Twitch refused {}: {}", subscription.kind, e);
+                Created::Refused
+            } else {
+                error!("[EventSub] Failed to subscribe to {}, will retry: {}", subscription.kind, e);
+                Created::Failed
+            }
+        }
+    }
+}
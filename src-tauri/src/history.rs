@@ -0,0 +1,67 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::chat::ChatMessage;
+
+/// Messages kept per channel
+pub const MAX_HISTORY_PER_CHANNEL: usize = 1000;
+
+/// Recent chat messages per channel login, so the UI can repopulate after a reload
+/// or a channel switch and scroll back
+#[derive(Default)]
+pub struct ChatHistory {
+    channels: HashMap<String, VecDeque<ChatMessage>>,
+}
+
+impl ChatHistory {
+    pub fn push(&mut self, message: ChatMessage) {
+        let messages = self.channels.entry(message.channel.clone()).or_default();
+        messages.push_back(message);
+        if messages.len() > MAX_HISTORY_PER_CHANNEL {
+            messages.pop_front();
+        }
+    }
+
+    /// Prepend messages sent before the oldest one we have, skipping those already stored.
+    /// Returns the messages actually added, oldest first.
+    pub fn backfill(&mut self, channel: &str, older: Vec<ChatMessage>) -> Vec<ChatMessage> {
+        let messages = self.channels.entry(channel.to_string()).or_default();
+        let oldest = messages.front().map(|m| m.timestamp);
+        let added: Vec<ChatMessage> = older.into_iter()
+            .filter(|m| oldest.is_none_or(|t| m.timestamp <= t))
+            .filter(|m| !messages.iter().any(|known| known.id == m.id))
+            .collect();
+        for message in added.iter().rev() {
+            messages.push_front(message.clone());
+        }
+        while messages.len() > MAX_HISTORY_PER_CHANNEL {
+            messages.pop_front();
+        }
+        added
+    }
+
+    /// A message of `channel` still in the buffer
+    pub fn find(&self, channel: &str, id: &str) -> Option<&ChatMessage> {
+        self.channels.get(channel)?.iter().rev().find(|m| m.id == id)
+    }
+
+    pub fn is_empty(&self, channel: &str) -> bool {
+        self.channels.get(channel).is_none_or(|m| m.is_empty())
+    }
+
+    /// Up to `limit` messages of `channel` sent before `before_id` (or the latest ones), oldest first.
+    /// Empty when `before_id` is no longer in the buffer.
+    pub fn before(&self, channel: &str, before_id: Option<&str>, limit: usize) -> Vec<ChatMessage> {
+        let Some(messages) = self.channels.get(channel) else {
+            return Vec::new();
+        };
+        let end = match before_id {
+            Some(id) => match messages.iter().position(|m| m.id == id) {
+                Some(index) => index,
+                None => return Vec::new(),
+            },
+            None => messages.len(),
+        };
+        let start = end.saturating_sub(limit);
+        messages.range(start..end).cloned().collect()
+    }
+}
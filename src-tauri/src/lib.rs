@@ -1,12 +1,25 @@
 pub mod twitch;
+pub mod automod;
 pub mod chat;
+pub mod chat_export;
+pub mod chat_log;
+pub mod chatters;
 pub mod emotes;
+pub mod eventsub;
+pub mod history;
+pub mod ratelimit;
+pub mod recent_messages;
+pub mod slash_commands;
+pub mod user_card;
+pub mod vod_chat;
+pub mod whispers;
 
 use log::{info, debug, error};
 use tauri::{State, Window, Manager, Emitter};
-use twitch::TwitchClient;
+use twitch::{ChannelInformation, ChannelInformationUpdate, ChatSettings, ChatSettingsUpdate, TwitchClient};
 use tokio::sync::Mutex;
 use emotes::Emote;
+use slash_commands::SlashCommand;
 use tauri_plugin_store::StoreExt;
 use std::sync::Arc;
 
@@ -20,10 +33,22 @@ pub struct WatchState {
 pub struct AppState {
     pub twitch_client: Mutex<TwitchClient>,
     pub http_client: Arc<reqwest::Client>,
-    pub chat_handle: Mutex<Option<tauri::async_runtime::JoinHandle<()>>>,
-    pub chat_sender: Mutex<Option<tokio::sync::mpsc::Sender<String>>>,
+    pub chat_connection: Mutex<Option<chat::ChatConnection>>,
     pub watch_state: Mutex<Option<WatchState>>,
     pub cached_username: Mutex<Option<String>>,
+    pub cached_user_id: Mutex<Option<String>>,
+    pub chat_state: chat::SharedChatState,
+    pub eventsub: Mutex<Option<eventsub::EventSubConnection>>,
+    pub whispers: Mutex<whispers::WhisperStore>,
+    pub automod_queue: Mutex<automod::AutomodQueue>,
+    /// Send chat messages through Helix instead of IRC
+    pub send_via_helix: Mutex<bool>,
+    pub recent_messages: Mutex<recent_messages::RecentMessagesSettings>,
+    /// `None` when the log database could not be opened
+    pub chat_log: Option<chat_log::ChatLog>,
+    pub chat_log_settings: Mutex<chat_log::ChatLogSettings>,
+    /// Chat replay of the VOD being played
+    pub vod_chat: Mutex<Option<vod_chat::VodChatReplay>>,
 }
 
 #[tauri::command]
@@ -61,6 +86,9 @@ async fn get_self_info(state: State<'_, AppState>) -> Result<serde_json::Value,
     if let Some(username) = result.get("viewer").and_then(|v| v.get("login")).and_then(|l| l.as_str()) {
         *state.cached_username.lock().await = Some(username.to_string());
     }
+    if let Some(user_id) = result.get("viewer").and_then(|v| v.get("id")).and_then(|i| i.as_str()) {
+        *state.cached_user_id.lock().await = Some(user_id.to_string());
+    }
 
     Ok(result)
 }
@@ -118,21 +146,7 @@ async fn get_twitch_channel_emotes(state: State<'_, AppState>, channel_id: Strin
 }
 
 #[tauri::command]
-async fn connect_to_chat(state: State<'_, AppState>, window: Window, channel: String) -> Result<(), String> {
-    // Abort existing chat connection
-    {
-        let mut handle_lock = state.chat_handle.lock().await;
-        if let Some(handle) = handle_lock.take() {
-            handle.abort();
-        }
-    }
-    
-    // Clear existing sender
-    {
-        let mut sender_lock = state.chat_sender.lock().await;
-        *sender_lock = None;
-    }
-    
+async fn join_chat(state: State<'_, AppState>, window: Window, channel: String) -> Result<(), String> {
     // Get auth info for authenticated chat
     let access_token = {
         let client = state.twitch_client.lock().await;
@@ -143,59 +157,745 @@ async fn connect_to_chat(state: State<'_, AppState>, window: Window, channel: St
     let username: Option<String> = if access_token.is_some() {
         let cached = state.cached_username.lock().await.clone();
         if let Some(user) = cached {
-            info!("[connect_to_chat] Using cached username: {}", user);
+            info!("[join_chat] Using cached username: {}", user);
             Some(user)
         } else {
             let client = state.twitch_client.lock().await.clone();
             match client.get_self_info().await {
                 Ok(data) => {
                     let username = data.get("viewer").and_then(|v| v.get("login")).and_then(|l| l.as_str()).map(|s| s.to_string());
-                    info!("[connect_to_chat] Got username from API: {:?}", username);
+                    info!("[join_chat] Got username from API: {:?}", username);
                     if let Some(ref user) = username {
                         *state.cached_username.lock().await = Some(user.clone());
                     }
                     username
                 },
                 Err(e) => {
-                    error!("[connect_to_chat] Failed to get self_info: {}", e);
+                    error!("[join_chat] Failed to get self_info: {}", e);
                     None
                 },
             }
         }
     } else {
-        info!("[connect_to_chat] No access_token, connecting anonymously");
+        info!("[join_chat] No access_token, connecting anonymously");
         None
     };
+    let login = username.as_ref().map(|u| u.to_lowercase());
     
-    // Connect to chat
-    match chat::connect_chat(channel.clone(), window, access_token, username).await {
-        Ok(connection) => {
-            let mut sender_lock = state.chat_sender.lock().await;
-            *sender_lock = Some(connection.sender);
-            info!("Chat connected to #{}", channel);
-            Ok(())
+    let mut connection_lock = state.chat_connection.lock().await;
+
+    // Reuse the connection unless it ended or the login changed; a new one rejoins every joined channel
+    if !connection_lock.as_ref().is_some_and(|c| c.is_usable_for(&login, &access_token)) {
+        info!("[join_chat] Opening chat connection as {:?}", login);
+        if connection_lock.as_ref().is_some_and(|c| c.login != login) {
+            state.chat_state.lock().await.forget_identity();
         }
-        Err(e) => {
-            error!("Chat connection error: {}", e);
-            Err(e.to_string())
+        *connection_lock = Some(chat::connect_chat(window, access_token, username, state.chat_state.clone()));
+    }
+
+    let connection = connection_lock.as_ref().ok_or("Not connected to chat")?;
+    connection.join(&channel).await.map_err(|e| e.to_string())?;
+    drop(connection_lock);
+
+    backfill_recent_messages(&state, &channel.to_lowercase()).await;
+    Ok(())
+}
+
+/// Load what was said before we joined into the chat history, when enabled and the
+/// channel has no history yet. Failures only cost the backfill, never the join.
+async fn backfill_recent_messages(state: &AppState, channel: &str) {
+    let settings = state.recent_messages.lock().await.clone();
+    if !settings.enabled || !state.chat_state.lock().await.history.is_empty(channel) {
+        return;
+    }
+    match recent_messages::fetch_recent_messages(&state.http_client, &settings.base_url, channel, recent_messages::BACKFILL_LIMIT).await {
+        Ok(messages) => {
+            let added = state.chat_state.lock().await.backfill(channel, messages);
+            info!("[join_chat] Backfilled {} messages in #{}", added, channel);
         }
+        Err(e) => error!("[join_chat] Failed to load recent messages for #{}: {}", channel, e),
     }
 }
 
+/// Drop the chat connection of a previous account or token. Its unacknowledged messages fail
+/// now instead of waiting for the next connection, whose USERSTATE would acknowledge them, and
+/// our user state goes with it until the next connection sends its own.
+async fn close_chat_connection(state: &AppState) {
+    *state.chat_connection.lock().await = None;
+    let mut chat_state = state.chat_state.lock().await;
+    chat_state.fail_pending();
+    chat_state.forget_identity();
+}
+
 #[tauri::command]
-async fn send_chat_message(state: State<'_, AppState>, message: String) -> Result<(), String> {
-    debug!("[send_chat_message] Attempting to send: {}", message);
-    let sender_lock = state.chat_sender.lock().await;
-    if let Some(sender) = &*sender_lock {
-        debug!("[send_chat_message] Sender found, sending message");
-        sender.send(message).await.map_err(|e| {
+async fn part_chat(state: State<'_, AppState>, channel: String) -> Result<(), String> {
+    let connection_lock = state.chat_connection.lock().await;
+    match &*connection_lock {
+        Some(connection) => connection.part(&channel).await.map_err(|e| e.to_string()),
+        None => {
+            state.chat_state.lock().await.joined.remove(&channel.to_lowercase());
+            Ok(())
+        }
+    }
+}
+
+#[tauri::command]
+async fn get_joined_channels(state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    Ok(state.chat_state.lock().await.joined.iter().cloned().collect())
+}
+
+/// Whether chat messages go through Helix: when selected, or when IRC can't send because
+/// we are anonymous or disconnected
+async fn sends_via_helix(state: &AppState) -> bool {
+    let logged_in_irc = state.chat_connection.lock().await.as_ref().is_some_and(|c| c.login.is_some());
+    let irc_usable = logged_in_irc && state.chat_state.lock().await.connected;
+    *state.send_via_helix.lock().await || !irc_usable
+}
+
+/// Queue a message on the chat connection and wait for the rate limiter and Twitch's answer
+async fn send_to_chat(
+    state: &AppState,
+    channel: String,
+    message: String,
+    reply_parent_id: Option<String>,
+    nonce: Option<String>,
+) -> Result<chat::SendResult, String> {
+    if sends_via_helix(state).await {
+        return send_via_helix(state, &channel, &message, reply_parent_id.as_deref()).await;
+    }
+
+    let result = {
+        let connection_lock = state.chat_connection.lock().await;
+        let Some(connection) = &*connection_lock else {
+            error!("[send_chat_message] Not connected to chat");
+            return Err("Not connected to chat".to_string());
+        };
+        let nonce = nonce.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        connection.send(&channel, message, reply_parent_id, nonce).await.map_err(|e| {
             error!("[send_chat_message] Send error: {}", e);
             e.to_string()
-        })
+        })?
+    };
+
+    // Wait for the rate limiter, then for Twitch to acknowledge (USERSTATE) or reject (NOTICE) the message
+    Ok(result.await.unwrap_or_else(|_| {
+        chat::SendResult::failed(chat::SendFailure::Disconnected, "Chat connection closed")
+    }))
+}
+
+/// Our Twitch user id, cached after the first Helix lookup
+async fn own_user_id(state: &AppState) -> Result<String, String> {
+    if let Some(user_id) = state.cached_user_id.lock().await.clone() {
+        return Ok(user_id);
+    }
+    let client = state.twitch_client.lock().await.clone();
+    if !client.is_authenticated() {
+        return Err("Not logged in".to_string());
+    }
+    let info = client.get_self_info().await.map_err(|e| e.to_string())?;
+    let user_id = info.get("viewer").and_then(|v| v.get("id")).and_then(|i| i.as_str())
+        .map(|id| id.to_string())
+        .ok_or("Could not determine your user id")?;
+    if let Some(username) = info.get("viewer").and_then(|v| v.get("login")).and_then(|l| l.as_str()) {
+        *state.cached_username.lock().await = Some(username.to_string());
+    }
+    *state.cached_user_id.lock().await = Some(user_id.clone());
+    Ok(user_id)
+}
+
+/// Broadcaster id of a channel, from its ROOMSTATE when joined or from Helix otherwise
+async fn channel_id(state: &AppState, channel: &str) -> Result<String, String> {
+    let channel = channel.to_lowercase();
+    if let Some(room_id) = state.chat_state.lock().await.rooms.get(&channel).and_then(|r| r.room_id.clone()) {
+        return Ok(room_id);
+    }
+    let client = state.twitch_client.lock().await.clone();
+    client.get_user_id(&channel).await.map_err(|e| e.to_string())
+}
+
+/// Run a slash command typed in `channel`: `/me` over IRC, the others through Helix
+async fn run_slash_command(
+    state: &AppState,
+    window: &Window,
+    channel: String,
+    command: SlashCommand,
+    nonce: Option<String>,
+) -> Result<chat::SendResult, String> {
+    let message = match command {
+        // `/me` is the only command Twitch still honors over IRC
+        SlashCommand::Me(text) => {
+            // Helix has no CTCP, the ACTION markers would be posted as text
+            if sends_via_helix(state).await {
+                return Err("/me needs the IRC chat connection and can't be sent through Helix".to_string());
+            }
+            return send_to_chat(state, channel, format!("\u{1}ACTION {}\u{1}", text), None, nonce).await;
+        }
+        SlashCommand::Ban { user, reason } => {
+            let (client, broadcaster_id, moderator_id) = moderation_context(state, &channel).await?;
+            let target = client.get_user_id(&user).await.map_err(|e| e.to_string())?;
+            client.ban_user(&broadcaster_id, &moderator_id, &target, None, reason.as_deref()).await.map_err(|e| e.to_string())?;
+            format!("{} is now banned", user)
+        }
+        SlashCommand::Timeout { user, duration_secs, reason } => {
+            let (client, broadcaster_id, moderator_id) = moderation_context(state, &channel).await?;
+            let target = client.get_user_id(&user).await.map_err(|e| e.to_string())?;
+            client.ban_user(&broadcaster_id, &moderator_id, &target, Some(duration_secs), reason.as_deref()).await.map_err(|e| e.to_string())?;
+            format!("{} is timed out for {} seconds", user, duration_secs)
+        }
+        SlashCommand::Unban { user } => {
+            let (client, broadcaster_id, moderator_id) = moderation_context(state, &channel).await?;
+            let target = client.get_user_id(&user).await.map_err(|e| e.to_string())?;
+            client.unban_user(&broadcaster_id, &moderator_id, &target).await.map_err(|e| e.to_string())?;
+            format!("{} is no longer banned", user)
+        }
+        SlashCommand::Clear => {
+            let (client, broadcaster_id, moderator_id) = moderation_context(state, &channel).await?;
+            client.delete_chat_messages(&broadcaster_id, &moderator_id, None).await.map_err(|e| e.to_string())?;
+            "Chat cleared".to_string()
+        }
+        SlashCommand::Slow(wait) => {
+            let (client, broadcaster_id, moderator_id) = moderation_context(state, &channel).await?;
+            let settings = ChatSettingsUpdate {
+                slow_mode: Some(wait.is_some()),
+                slow_mode_wait_time: wait,
+                ..Default::default()
+            };
+            client.update_chat_settings(&broadcaster_id, &moderator_id, &settings).await.map_err(|e| e.to_string())?;
+            match wait {
+                Some(secs) => format!("Slow mode set to {} seconds", secs),
+                None => "Slow mode is off".to_string(),
+            }
+        }
+        SlashCommand::Whisper { user, message } => {
+            deliver_whisper(state, &user, &message).await?;
+            format!("Whisper sent to {}", user)
+        }
+        SlashCommand::Raid(target_channel) => {
+            let (client, own_id) = broadcaster_context(state).await?;
+            if channel_id(state, &channel).await? != own_id {
+                return Err("Only the broadcaster can start a raid".to_string());
+            }
+            let target = client.get_user_id(&target_channel).await.map_err(|e| e.to_string())?;
+            client.start_raid(&own_id, &target).await.map_err(|e| e.to_string())?;
+            format!("Raiding {}", target_channel)
+        }
+        SlashCommand::Announce { message, color } => {
+            let (client, broadcaster_id, moderator_id) = moderation_context(state, &channel).await?;
+            client.send_announcement(&broadcaster_id, &moderator_id, &message, color.as_deref()).await.map_err(|e| e.to_string())?;
+            "Announcement sent".to_string()
+        }
+        SlashCommand::Color(color) => {
+            let hex = apply_chat_color(state, window, &color).await?;
+            format!("Your color is now {}", hex)
+        }
+    };
+    Ok(chat::SendResult::Executed { message })
+}
+
+/// Change our chat color through Helix, then update our cached user states so the local echo
+/// of our next messages already uses it. Returns the new color as `#RRGGBB`.
+async fn apply_chat_color(state: &AppState, window: &Window, color: &str) -> Result<String, String> {
+    let (value, hex) = twitch::resolve_chat_color(color).map_err(|e| e.to_string())?;
+    let client = state.twitch_client.lock().await.clone();
+    if !client.is_authenticated() {
+        return Err("Must be logged in to change your color".to_string());
+    }
+    let own_id = own_user_id(state).await?;
+    client.update_chat_color(&own_id, &value).await.map_err(|e| if value.starts_with('#') {
+        format!("Could not set {} (hex colors require Turbo or Prime Gaming): {}", value, e)
     } else {
-        error!("[send_chat_message] Not connected to chat");
-        Err("Not connected to chat".to_string())
+        e.to_string()
+    })?;
+
+    let updated = state.chat_state.lock().await.set_own_color(&hex);
+    for user_state in updated {
+        let _ = window.emit("chat-user-state", user_state);
     }
+    Ok(hex)
+}
+
+#[tauri::command]
+async fn get_chat_color(state: State<'_, AppState>) -> Result<Option<String>, String> {
+    let client = state.twitch_client.lock().await.clone();
+    if !client.is_authenticated() {
+        return Err("Must be logged in to read your color".to_string());
+    }
+    let own_id = own_user_id(&state).await?;
+    client.get_chat_color(&own_id).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn set_chat_color(state: State<'_, AppState>, window: Window, color: String) -> Result<String, String> {
+    apply_chat_color(&state, &window, &color).await
+}
+
+/// Connect EventSub for the logged-in account and route its events to the frontend
+async fn start_eventsub(handle: &tauri::AppHandle) -> Result<(), String> {
+    let state = handle.state::<AppState>();
+    let client = state.twitch_client.lock().await.clone();
+    if !client.is_authenticated() {
+        return Ok(());
+    }
+    let user_id = own_user_id(&state).await?;
+
+    let subscriptions = vec![
+        eventsub::Subscription::new("user.whisper.message", "1", serde_json::json!({ "user_id": user_id })),
+    ];
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut eventsub = state.eventsub.lock().await;
+    // A refreshed token of the same account keeps its whispers and held messages
+    if eventsub.as_ref().is_none_or(|e| e.user_id != user_id) {
+        *state.whispers.lock().await = Default::default();
+        *state.automod_queue.lock().await = Default::default();
+    }
+    *eventsub = Some(eventsub::connect_eventsub(client, &user_id, subscriptions, tx));
+    drop(eventsub);
+
+    // Ends when the connection is dropped on logout or account change
+    let handle = handle.clone();
+    tauri::async_runtime::spawn(async move {
+        while let Some(notification) = rx.recv().await {
+            handle_eventsub_notification(&handle, notification).await;
+        }
+    });
+    Ok(())
+}
+
+async fn handle_eventsub_notification(handle: &tauri::AppHandle, notification: eventsub::Notification) {
+    let state = handle.state::<AppState>();
+    match notification.kind.as_str() {
+        "user.whisper.message" => {
+            let received = {
+                let mut whispers = state.whispers.lock().await;
+                whispers.add_incoming(&notification.event).map(|whisper| (whisper, whispers.unread()))
+            };
+            if let Some((whisper, unread)) = received {
+                let _ = handle.emit("whisper-received", whisper);
+                let _ = handle.emit("whisper-unread", unread);
+            }
+        }
+        "automod.message.hold" => {
+            let held = state.automod_queue.lock().await.add_hold(&notification.event);
+            if let Some(held) = held {
+                let _ = handle.emit("automod-held", held);
+            }
+        }
+        "automod.message.update" => {
+            let resolution = state.automod_queue.lock().await.apply_update(&notification.event);
+            if let Some(resolution) = resolution {
+                let _ = handle.emit("automod-resolved", resolution);
+            }
+        }
+        other => debug!("[EventSub] Unhandled notification: {}", other),
+    }
+}
+
+#[tauri::command]
+async fn get_automod_queue(state: State<'_, AppState>, channel_id: Option<String>) -> Result<Vec<automod::HeldMessage>, String> {
+    Ok(state.automod_queue.lock().await.list(channel_id.as_deref()))
+}
+
+#[tauri::command]
+async fn approve_automod_message(state: State<'_, AppState>, window: Window, message_id: String) -> Result<(), String> {
+    resolve_automod_message(&state, &window, &message_id, true).await
+}
+
+#[tauri::command]
+async fn deny_automod_message(state: State<'_, AppState>, window: Window, message_id: String) -> Result<(), String> {
+    resolve_automod_message(&state, &window, &message_id, false).await
+}
+
+async fn resolve_automod_message(state: &AppState, window: &Window, message_id: &str, allow: bool) -> Result<(), String> {
+    let client = state.twitch_client.lock().await.clone();
+    if !client.is_authenticated() {
+        return Err("Must be logged in to moderate".to_string());
+    }
+    let moderator_id = own_user_id(state).await?;
+    client.manage_held_automod_message(&moderator_id, message_id, allow).await.map_err(|e| e.to_string())?;
+
+    // Resolve right away instead of waiting for `automod.message.update`
+    let status = if allow { automod::AutomodStatus::Approved } else { automod::AutomodStatus::Denied };
+    let moderator_login = state.cached_username.lock().await.clone();
+    let resolution = state.automod_queue.lock().await.resolve(message_id, status, moderator_login);
+    if let Some(resolution) = resolution {
+        let _ = window.emit("automod-resolved", resolution);
+    }
+    Ok(())
+}
+
+/// Send a whisper through Helix and add it to its conversation
+async fn deliver_whisper(state: &AppState, to_login: &str, message: &str) -> Result<whispers::Whisper, String> {
+    let client = state.twitch_client.lock().await.clone();
+    if !client.is_authenticated() {
+        return Err("Must be logged in to whisper".to_string());
+    }
+    let own_id = own_user_id(state).await?;
+    let own_login = state.cached_username.lock().await.clone().unwrap_or_default();
+    let to_login = to_login.trim_start_matches('@').to_lowercase();
+    let to_user_id = client.get_user_id(&to_login).await.map_err(|e| e.to_string())?;
+    client.send_whisper(&own_id, &to_user_id, message).await.map_err(|e| e.to_string())?;
+    let own_display_name = own_display_name(state, &client, &own_login).await;
+    Ok(state.whispers.lock().await.add_outgoing(&to_user_id, &to_login, &own_id, &own_login, &own_display_name, message))
+}
+
+/// Our display name from GLOBALUSERSTATE, or from Helix before chat told us, else our login
+async fn own_display_name(state: &AppState, client: &TwitchClient, login: &str) -> String {
+    let from_chat = state.chat_state.lock().await.global_user_state.display_name.clone();
+    if let Some(display_name) = from_chat.filter(|d| !d.is_empty()) {
+        return display_name;
+    }
+    client.get_self_info().await.ok()
+        .and_then(|info| info["viewer"]["displayName"].as_str().map(|d| d.to_string()))
+        .filter(|d| !d.is_empty())
+        .unwrap_or_else(|| login.to_string())
+}
+
+#[tauri::command]
+async fn send_whisper(state: State<'_, AppState>, to_login: String, message: String) -> Result<whispers::Whisper, String> {
+    deliver_whisper(&state, &to_login, &message).await
+}
+
+#[tauri::command]
+async fn get_whisper_conversations(state: State<'_, AppState>) -> Result<Vec<whispers::Conversation>, String> {
+    Ok(state.whispers.lock().await.conversations())
+}
+
+#[tauri::command]
+async fn get_whisper_conversation(state: State<'_, AppState>, user_id: String) -> Result<Option<whispers::Conversation>, String> {
+    Ok(state.whispers.lock().await.conversation(&user_id))
+}
+
+#[tauri::command]
+async fn mark_whispers_read(state: State<'_, AppState>, window: Window, user_id: String) -> Result<whispers::WhisperUnread, String> {
+    let unread = {
+        let mut whispers = state.whispers.lock().await;
+        whispers.mark_read(&user_id);
+        whispers.unread()
+    };
+    let _ = window.emit("whisper-unread", unread.clone());
+    Ok(unread)
+}
+
+/// Send through Helix `/chat/messages`. Our IRC connection receives the message like any other,
+/// so there is no local echo here.
+async fn send_via_helix(
+    state: &AppState,
+    channel: &str,
+    message: &str,
+    reply_parent_id: Option<&str>,
+) -> Result<chat::SendResult, String> {
+    let client = state.twitch_client.lock().await.clone();
+    if !client.is_authenticated() {
+        return Err("Must be logged in to chat".to_string());
+    }
+    let broadcaster_id = channel_id(state, channel).await?;
+    let sender_id = own_user_id(state).await?;
+    let result = client.send_chat_message(&broadcaster_id, &sender_id, message, reply_parent_id).await
+        .map_err(|e| {
+            error!("[send_chat_message] Helix send error: {}", e);
+            e.to_string()
+        })?;
+
+    if result.get("is_sent").and_then(|s| s.as_bool()).unwrap_or(false) {
+        let message_id = result.get("message_id").and_then(|id| id.as_str()).unwrap_or_default();
+        return Ok(chat::SendResult::Sent { message_id: message_id.to_string() });
+    }
+    let drop_reason = result.get("drop_reason");
+    let code = drop_reason.and_then(|r| r.get("code")).and_then(|c| c.as_str()).unwrap_or_default();
+    let text = drop_reason.and_then(|r| r.get("message")).and_then(|m| m.as_str()).unwrap_or("Message was not sent");
+    let reason = chat::SendFailure::from_msg_id(code).unwrap_or(chat::SendFailure::Rejected);
+    Ok(chat::SendResult::failed(reason, text))
+}
+
+#[tauri::command]
+async fn get_send_via_helix(state: State<'_, AppState>) -> Result<bool, String> {
+    Ok(*state.send_via_helix.lock().await)
+}
+
+#[tauri::command]
+async fn set_send_via_helix(state: State<'_, AppState>, handle: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    *state.send_via_helix.lock().await = enabled;
+    if let Ok(store) = handle.store("settings.bin") {
+        store.set("send_via_helix", serde_json::Value::Bool(enabled));
+        let _ = store.save();
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_recent_messages_settings(state: State<'_, AppState>) -> Result<recent_messages::RecentMessagesSettings, String> {
+    Ok(state.recent_messages.lock().await.clone())
+}
+
+#[tauri::command]
+async fn set_recent_messages_settings(
+    state: State<'_, AppState>,
+    handle: tauri::AppHandle,
+    settings: recent_messages::RecentMessagesSettings,
+) -> Result<(), String> {
+    let base_url = settings.base_url.trim();
+    let settings = recent_messages::RecentMessagesSettings {
+        enabled: settings.enabled,
+        base_url: if base_url.is_empty() {
+            recent_messages::DEFAULT_RECENT_MESSAGES_URL.to_string()
+        } else {
+            url::Url::parse(base_url).map_err(|e| format!("Invalid recent messages URL: {}", e))?;
+            base_url.to_string()
+        },
+    };
+    if let Ok(store) = handle.store("settings.bin") {
+        store.set("recent_messages", serde_json::to_value(&settings).map_err(|e| e.to_string())?);
+        let _ = store.save();
+    }
+    *state.recent_messages.lock().await = settings;
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_chat_log_settings(state: State<'_, AppState>) -> Result<chat_log::ChatLogSettings, String> {
+    Ok(state.chat_log_settings.lock().await.clone())
+}
+
+#[tauri::command]
+async fn set_chat_log_settings(
+    state: State<'_, AppState>,
+    handle: tauri::AppHandle,
+    settings: chat_log::ChatLogSettings,
+) -> Result<(), String> {
+    if settings.retention_days == Some(0) {
+        return Err("Retention must be at least one day".to_string());
+    }
+    if let Ok(store) = handle.store("settings.bin") {
+        store.set("chat_logs", serde_json::to_value(&settings).map_err(|e| e.to_string())?);
+        let _ = store.save();
+    }
+    state.chat_state.lock().await.chat_log = state.chat_log.clone().filter(|_| settings.enabled);
+    *state.chat_log_settings.lock().await = settings;
+    prune_chat_logs(&state).await;
+    Ok(())
+}
+
+/// Apply the retention policy to the chat log
+async fn prune_chat_logs(state: &AppState) {
+    let Some(log) = state.chat_log.clone() else { return };
+    let Some(retention_days) = state.chat_log_settings.lock().await.retention_days else { return };
+    let now = chrono::Utc::now().timestamp_millis();
+    match tokio::task::spawn_blocking(move || log.prune(retention_days, now)).await {
+        Ok(Ok(0)) => {}
+        Ok(Ok(removed)) => info!("[ChatLog] Removed {} messages older than {} days", removed, retention_days),
+        Ok(Err(e)) => error!("[ChatLog] Failed to apply retention: {}", e),
+        Err(e) => error!("[ChatLog] Retention task failed: {}", e),
+    }
+}
+
+fn chat_log(state: &AppState) -> Result<chat_log::ChatLog, String> {
+    state.chat_log.clone().ok_or_else(|| "Chat logs are unavailable".to_string())
+}
+
+#[tauri::command]
+async fn search_chat_logs(state: State<'_, AppState>, query: chat_log::ChatLogQuery) -> Result<Vec<chat::ChatMessage>, String> {
+    let log = chat_log(&state)?;
+    tokio::task::spawn_blocking(move || log.search(&query))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_chat_log_days(state: State<'_, AppState>, channel: Option<String>) -> Result<Vec<chat_log::ChatLogDay>, String> {
+    let log = chat_log(&state)?;
+    tokio::task::spawn_blocking(move || log.days(channel.as_deref()))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn export_chat_logs(
+    state: State<'_, AppState>,
+    channel: String,
+    from: i64,
+    to: i64,
+    format: chat_export::ExportFormat,
+    path: String,
+) -> Result<chat_export::ExportSummary, String> {
+    if from >= to {
+        return Err("The export range is empty".to_string());
+    }
+    let log = chat_log(&state)?;
+    let channel = channel.trim_start_matches('#').to_lowercase();
+    tokio::task::spawn_blocking(move || {
+        let messages = log.range(&channel, from, to)?;
+        if messages.is_empty() {
+            return Err(anyhow::anyhow!("No messages logged in #{} for that range", channel));
+        }
+        chat_export::export(&channel, &messages, format, std::path::Path::new(&path))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
+
+/// Comments of `video_id` due at `position` seconds since the previous call. Call it as playback
+/// advances; a new video or a seek restarts the replay (`reset` in the update).
+#[tauri::command]
+async fn sync_vod_chat(state: State<'_, AppState>, video_id: String, position: f64) -> Result<vod_chat::VodChatUpdate, String> {
+    let client = state.twitch_client.lock().await.clone();
+    let mut replay = state.vod_chat.lock().await;
+    if replay.as_ref().map(|r| r.video_id.as_str()) != Some(video_id.as_str()) {
+        info!("[VodChat] Starting replay of video {}", video_id);
+        *replay = Some(vod_chat::VodChatReplay::new(&video_id));
+    }
+    let replay = replay.as_mut().ok_or("No VOD chat replay")?;
+    replay.sync(&client, position).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn stop_vod_chat(state: State<'_, AppState>) -> Result<(), String> {
+    *state.vod_chat.lock().await = None;
+    Ok(())
+}
+
+#[tauri::command]
+async fn send_chat_message(
+    state: State<'_, AppState>,
+    window: Window,
+    channel: String,
+    message: String,
+    nonce: Option<String>,
+) -> Result<chat::SendResult, String> {
+    debug!("[send_chat_message] Attempting to send to #{}: {}", channel, message);
+    match slash_commands::parse(&message) {
+        Some(command) => run_slash_command(&state, &window, channel, command?, nonce).await,
+        None => send_to_chat(&state, channel, message, None, nonce).await,
+    }
+}
+
+#[tauri::command]
+async fn send_chat_reply(
+    state: State<'_, AppState>,
+    channel: String,
+    parent_id: String,
+    message: String,
+    nonce: Option<String>,
+) -> Result<chat::SendResult, String> {
+    // The id ends up in an IRC tag, so anything but a message id could break the line
+    if uuid::Uuid::parse_str(&parent_id).is_err() {
+        return Err(format!("Invalid message id to reply to: {}", parent_id));
+    }
+    debug!("[send_chat_reply] Replying to {} in #{}: {}", parent_id, channel, message);
+    send_to_chat(&state, channel, message, Some(parent_id), nonce).await
+}
+
+#[tauri::command]
+async fn get_reply_thread(state: State<'_, AppState>, thread_id: String) -> Result<Option<chat::ReplyThread>, String> {
+    let chat_state = state.chat_state.lock().await;
+    Ok(chat_state.threads.get(&thread_id).cloned())
+}
+
+#[tauri::command]
+async fn get_room_state(state: State<'_, AppState>, channel: String) -> Result<Option<chat::RoomState>, String> {
+    let chat_state = state.chat_state.lock().await;
+    Ok(chat_state.rooms.get(&channel.to_lowercase()).cloned())
+}
+
+/// Chatters of a joined channel. Moderators get paginated Helix results; everyone else
+/// gets the users seen talking since joining.
+#[tauri::command]
+async fn get_chatters(
+    state: State<'_, AppState>,
+    channel: String,
+    after: Option<String>,
+    first: Option<u32>,
+) -> Result<chatters::ChatterList, String> {
+    let channel = channel.to_lowercase();
+    let (observed, is_moderator) = {
+        let chat_state = state.chat_state.lock().await;
+        let observed = chat_state.chatters.get(&channel).cloned().unwrap_or_default();
+        (observed, chat_state.user_state(&channel).is_moderator())
+    };
+
+    let client = state.twitch_client.lock().await.clone();
+    if !client.is_authenticated() || !is_moderator {
+        return Ok(chatters::ChatterList::from_observed(observed.into_values()));
+    }
+
+    let broadcaster_id = channel_id(&state, &channel).await?;
+    let moderator_id = own_user_id(&state).await?;
+    let page = client.get_chatters(&broadcaster_id, &moderator_id, first.unwrap_or(1000), after.as_deref()).await
+        .map_err(|e| e.to_string())?;
+    Ok(chatters::ChatterList::from_helix(&page, &observed, &channel))
+}
+
+/// Profile, follow age, subscription and recent messages of a chatter in a channel
+#[tauri::command]
+async fn get_user_card(state: State<'_, AppState>, channel_id: String, user_login: String) -> Result<user_card::UserCard, String> {
+    let user_login = user_login.trim_start_matches('@').to_lowercase();
+    let client = state.twitch_client.lock().await.clone();
+    let info = client.get_user_card_info(&user_login).await.map_err(|e| e.to_string())?;
+    let field = |name: &str| info.get(name).and_then(|v| v.as_str()).filter(|v| !v.is_empty()).map(|v| v.to_string());
+    let user_id = field("id").unwrap_or_default();
+
+    // Helix hides who follows from anyone but the broadcaster and moderators, so don't read
+    // "no follow" into its answer for others
+    let follow_age = if client.is_authenticated() && can_see_followers(&state, &channel_id).await {
+        client.get_follow_date(&channel_id, &user_id).await.ok()
+            .map(|followed_at| twitch::FollowAge::new(user_id.clone(), followed_at))
+    } else {
+        None
+    };
+
+    let recent_messages = {
+        let chat_state = state.chat_state.lock().await;
+        chat_state.channel_login(&channel_id)
+            .map(|channel| chat_state.messages_from(&channel, &user_login))
+            .unwrap_or_default()
+    };
+    let (badges, subscription) = match recent_messages.last() {
+        Some(last) => (last.badges.clone(), user_card::SubscriptionInfo::from_badges(&last.badges, &last.badge_info)),
+        None => (Vec::new(), None),
+    };
+
+    Ok(user_card::UserCard {
+        login: field("login").unwrap_or(user_login),
+        display_name: field("displayName").unwrap_or_default(),
+        description: field("description"),
+        profile_image_url: field("profileImageURL"),
+        created_at: field("createdAt"),
+        follow_visible: follow_age.is_some(),
+        followed_at: follow_age.as_ref().and_then(|f| f.followed_at.clone()),
+        follow_age_secs: follow_age.and_then(|f| f.follow_age_secs),
+        subscription,
+        badges,
+        recent_messages,
+        user_id,
+    })
+}
+
+/// Messages of `channel` before `before_id` (or the latest ones), oldest first
+#[tauri::command]
+async fn get_chat_history(
+    state: State<'_, AppState>,
+    channel: String,
+    before_id: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<chat::ChatMessage>, String> {
+    let limit = limit.unwrap_or(100).min(history::MAX_HISTORY_PER_CHANNEL);
+    let chat_state = state.chat_state.lock().await;
+    Ok(chat_state.history.before(&channel.to_lowercase(), before_id.as_deref(), limit))
+}
+
+#[tauri::command]
+async fn get_user_state(state: State<'_, AppState>, channel: String) -> Result<chat::UserState, String> {
+    let chat_state = state.chat_state.lock().await;
+    Ok(chat_state.user_state(&channel.to_lowercase()))
+}
+
+fn automod_subscriptions(broadcaster_id: &str, moderator_id: &str) -> Vec<eventsub::Subscription> {
+    let condition = serde_json::json!({ "broadcaster_user_id": broadcaster_id, "moderator_user_id": moderator_id });
+    ["automod.message.hold", "automod.message.update"].into_iter()
+        .map(|kind| eventsub::Subscription::new(kind, "1", condition.clone()))
+        .collect()
 }
 
 #[tauri::command]
@@ -206,6 +906,29 @@ async fn update_watch_state(
     stream_id: String, 
     user_id: String
 ) -> Result<(), String> {
+    // AutoMod holds of the watched channel, only when we moderate it since Twitch refuses them otherwise.
+    // Our USERSTATE may come after the first call, so this is re-checked on every refresh.
+    let previous = state.watch_state.lock().await.as_ref().map(|w| (w.channel_id.clone(), w.user_id.clone()));
+    let moderating = channel_id == user_id
+        || state.chat_state.lock().await.user_state(&channel_login.to_lowercase()).is_moderator();
+    if let Some(eventsub) = &*state.eventsub.lock().await {
+        let current = (channel_id.clone(), user_id.clone());
+        if let Some((previous_channel, previous_user)) = previous.filter(|p| *p != current || !moderating) {
+            for subscription in automod_subscriptions(&previous_channel, &previous_user) {
+                if let Err(e) = eventsub.unsubscribe(subscription).await {
+                    error!("[update_watch_state] Failed to drop AutoMod subscription: {}", e);
+                }
+            }
+        }
+        if moderating {
+            for subscription in automod_subscriptions(&channel_id, &user_id) {
+                if let Err(e) = eventsub.subscribe(subscription).await {
+                    error!("[update_watch_state] Failed to subscribe to AutoMod: {}", e);
+                }
+            }
+        }
+    }
+
     let mut watch_lock = state.watch_state.lock().await;
     *watch_lock = Some(WatchState {
         channel_login,
@@ -221,7 +944,7 @@ async fn login(handle: tauri::AppHandle) -> Result<(), String> {
     let scopes = [
         "channel:edit:commercial", "channel:manage:broadcast", "channel:manage:moderators",
         "channel:manage:raids", "channel:manage:vips", "channel:moderate", "chat:edit",
-        "chat:read", "moderator:manage:announcements", "moderator:manage:banned_users",
+        "chat:read", "moderator:manage:announcements", "moderator:manage:automod", "moderator:manage:banned_users",
         "moderator:manage:chat_messages", "moderator:manage:chat_settings", "moderator:read:chatters",
         "moderator:read:followers", "user:manage:chat_color", "user:manage:whispers",
         "user:read:chat", "user:read:email", "user:read:emotes", "user:read:follows", "user:write:chat",
@@ -277,6 +1000,10 @@ async fn login(handle: tauri::AppHandle) -> Result<(), String> {
                                     let mut client_lock = state.twitch_client.lock().await;
                                     let device_id = client_lock.get_device_id().to_string();
                                     *client_lock = TwitchClient::new(Some(token.clone()), Some(device_id));
+                                    *state.cached_username.lock().await = None;
+                                    *state.cached_user_id.lock().await = None;
+                                    // The old connection keeps the old token; the next join_chat reconnects
+                                    close_chat_connection(&state).await;
                                     info!("TwitchClient state updated with new token");
                                 }
                                 
@@ -287,6 +1014,10 @@ async fn login(handle: tauri::AppHandle) -> Result<(), String> {
                                     info!("Token saved to disk");
                                 }
                                 
+                                if let Err(e) = start_eventsub(&handle_clone).await {
+                                    error!("Failed to start EventSub: {}", e);
+                                }
+
                                 // Emit success event
                                 let _ = handle_clone.emit("login-success", token);
                                 
@@ -375,6 +1106,13 @@ async fn logout(state: State<'_, AppState>, handle: tauri::AppHandle) -> Result<
     let mut client_lock = state.twitch_client.lock().await;
     let device_id = client_lock.get_device_id().to_string();
     *client_lock = TwitchClient::new(None, Some(device_id));
+    *state.cached_username.lock().await = None;
+    *state.cached_user_id.lock().await = None;
+    // The chat connection belongs to the old account; the next join_chat reconnects anonymously
+    close_chat_connection(&state).await;
+    *state.eventsub.lock().await = None;
+    *state.whispers.lock().await = Default::default();
+    *state.automod_queue.lock().await = Default::default();
     if let Ok(store) = handle.store("settings.bin") {
         store.delete("access_token");
         let _ = store.save();
@@ -389,10 +1127,19 @@ async fn is_logged_in(state: State<'_, AppState>) -> Result<bool, String> {
 }
 
 #[tauri::command]
-async fn set_access_token(state: State<'_, AppState>, token: String) -> Result<(), String> {
-    let mut client_lock = state.twitch_client.lock().await;
-    let device_id = client_lock.get_device_id().to_string();
-    *client_lock = TwitchClient::new(Some(token), Some(device_id));
+async fn set_access_token(state: State<'_, AppState>, handle: tauri::AppHandle, token: String) -> Result<(), String> {
+    {
+        let mut client_lock = state.twitch_client.lock().await;
+        let device_id = client_lock.get_device_id().to_string();
+        *client_lock = TwitchClient::new(Some(token), Some(device_id));
+    }
+    *state.cached_username.lock().await = None;
+    *state.cached_user_id.lock().await = None;
+    // The old connection keeps the old token; the next join_chat reconnects
+    close_chat_connection(&state).await;
+    if let Err(e) = start_eventsub(&handle).await {
+        error!("Failed to start EventSub: {}", e);
+    }
     Ok(())
 }
 
@@ -420,6 +1167,232 @@ async fn unfollow_channel(state: State<'_, AppState>, from_user_id: String, to_u
     client.unfollow_user(&from_user_id, &to_user_id).await.map_err(|e| e.to_string())
 }
 
+/// Client plus the (broadcaster id, our user id) pair of `channel`, the chat the command comes from
+async fn moderation_context(state: &AppState, channel: &str) -> Result<(TwitchClient, String, String), String> {
+    let client = state.twitch_client.lock().await.clone();
+    if !client.is_authenticated() {
+        return Err("Must be logged in to moderate".to_string());
+    }
+    let broadcaster_id = channel_id(state, channel).await?;
+    let moderator_id = own_user_id(state).await?;
+    Ok((client, broadcaster_id, moderator_id))
+}
+
+#[tauri::command]
+async fn ban_user(state: State<'_, AppState>, channel: String, user_id: String, reason: Option<String>) -> Result<(), String> {
+    let (client, broadcaster_id, moderator_id) = moderation_context(&state, &channel).await?;
+    client.ban_user(&broadcaster_id, &moderator_id, &user_id, None, reason.as_deref()).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn timeout_user(
+    state: State<'_, AppState>,
+    channel: String,
+    user_id: String,
+    duration_secs: u32,
+    reason: Option<String>,
+) -> Result<(), String> {
+    if !(1..=slash_commands::MAX_TIMEOUT_SECS).contains(&duration_secs) {
+        return Err("Timeout duration must be between 1 second and 2 weeks".to_string());
+    }
+    let (client, broadcaster_id, moderator_id) = moderation_context(&state, &channel).await?;
+    client.ban_user(&broadcaster_id, &moderator_id, &user_id, Some(duration_secs), reason.as_deref()).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn unban_user(state: State<'_, AppState>, channel: String, user_id: String) -> Result<(), String> {
+    let (client, broadcaster_id, moderator_id) = moderation_context(&state, &channel).await?;
+    client.unban_user(&broadcaster_id, &moderator_id, &user_id).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn send_announcement(state: State<'_, AppState>, channel: String, message: String, color: Option<String>) -> Result<(), String> {
+    if message.trim().is_empty() {
+        return Err("Announcement cannot be empty".to_string());
+    }
+    let (client, broadcaster_id, moderator_id) = moderation_context(&state, &channel).await?;
+    client.send_announcement(&broadcaster_id, &moderator_id, message.trim(), color.as_deref()).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn send_shoutout(state: State<'_, AppState>, channel: String, to_login: String) -> Result<(), String> {
+    let (client, broadcaster_id, moderator_id) = moderation_context(&state, &channel).await?;
+    let to_broadcaster_id = client.get_user_id(&to_login.trim_start_matches('@').to_lowercase()).await.map_err(|e| e.to_string())?;
+    if to_broadcaster_id == broadcaster_id {
+        return Err("A channel cannot shout itself out".to_string());
+    }
+    client.send_shoutout(&broadcaster_id, &to_broadcaster_id, &moderator_id).await.map_err(|e| e.to_string())
+}
+
+/// Raid `to_login` from our own channel
+#[tauri::command]
+async fn start_raid(state: State<'_, AppState>, to_login: String) -> Result<serde_json::Value, String> {
+    let (client, own_id) = broadcaster_context(&state).await?;
+    let to_broadcaster_id = client.get_user_id(&to_login.trim_start_matches('@').to_lowercase()).await.map_err(|e| e.to_string())?;
+    client.start_raid(&own_id, &to_broadcaster_id).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn cancel_raid(state: State<'_, AppState>) -> Result<(), String> {
+    let (client, own_id) = broadcaster_context(&state).await?;
+    client.cancel_raid(&own_id).await.map_err(|e| e.to_string())
+}
+
+/// Client plus our own user id, for broadcaster-only endpoints acting on our channel
+async fn broadcaster_context(state: &AppState) -> Result<(TwitchClient, String), String> {
+    let client = state.twitch_client.lock().await.clone();
+    if !client.is_authenticated() {
+        return Err("Must be logged in to manage your channel".to_string());
+    }
+    let own_id = own_user_id(state).await?;
+    Ok((client, own_id))
+}
+
+#[tauri::command]
+async fn get_channel_information(state: State<'_, AppState>) -> Result<ChannelInformation, String> {
+    let (client, own_id) = broadcaster_context(&state).await?;
+    client.get_channel_information(&own_id).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn update_channel_information(state: State<'_, AppState>, update: ChannelInformationUpdate) -> Result<ChannelInformation, String> {
+    if update.title.as_deref().is_some_and(|t| t.trim().is_empty() || t.chars().count() > 140) {
+        return Err("Title must be between 1 and 140 characters".to_string());
+    }
+    if let Some(tags) = &update.tags {
+        if tags.len() > 10 {
+            return Err("A channel can have at most 10 tags".to_string());
+        }
+        if let Some(tag) = tags.iter().find(|t| t.is_empty() || t.chars().count() > 25 || !t.chars().all(char::is_alphanumeric)) {
+            return Err(format!("Invalid tag '{}': tags are up to 25 letters or digits, without spaces or symbols", tag));
+        }
+    }
+    let (client, own_id) = broadcaster_context(&state).await?;
+    client.modify_channel_information(&own_id, &update).await.map_err(|e| e.to_string())?;
+    client.get_channel_information(&own_id).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn search_categories(state: State<'_, AppState>, query: String, limit: Option<u32>) -> Result<Vec<twitch::Category>, String> {
+    let client = state.twitch_client.lock().await.clone();
+    if !client.is_authenticated() {
+        return Err("Must be logged in to search categories".to_string());
+    }
+    client.search_categories(&query, limit.unwrap_or(20)).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn start_commercial(state: State<'_, AppState>, length: u32) -> Result<twitch::Commercial, String> {
+    if !(1..=180).contains(&length) {
+        return Err("Commercial length must be between 1 and 180 seconds".to_string());
+    }
+    let (client, own_id) = broadcaster_context(&state).await?;
+    client.start_commercial(&own_id, length).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn create_stream_marker(state: State<'_, AppState>, description: Option<String>) -> Result<twitch::StreamMarker, String> {
+    if description.as_deref().is_some_and(|d| d.chars().count() > 140) {
+        return Err("Marker description must be at most 140 characters".to_string());
+    }
+    let (client, own_id) = broadcaster_context(&state).await?;
+    client.create_stream_marker(&own_id, description.as_deref()).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_moderators(state: State<'_, AppState>, after: Option<String>, first: Option<u32>) -> Result<twitch::UserPage, String> {
+    let (client, own_id) = broadcaster_context(&state).await?;
+    client.get_moderators(&own_id, first.unwrap_or(100), after.as_deref()).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn add_moderator(state: State<'_, AppState>, login: String) -> Result<(), String> {
+    let (client, own_id) = broadcaster_context(&state).await?;
+    let user_id = client.get_user_id(&login.trim_start_matches('@').to_lowercase()).await.map_err(|e| e.to_string())?;
+    client.add_moderator(&own_id, &user_id).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn remove_moderator(state: State<'_, AppState>, user_id: String) -> Result<(), String> {
+    let (client, own_id) = broadcaster_context(&state).await?;
+    client.remove_moderator(&own_id, &user_id).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_vips(state: State<'_, AppState>, after: Option<String>, first: Option<u32>) -> Result<twitch::UserPage, String> {
+    let (client, own_id) = broadcaster_context(&state).await?;
+    client.get_vips(&own_id, first.unwrap_or(100), after.as_deref()).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn add_vip(state: State<'_, AppState>, login: String) -> Result<(), String> {
+    let (client, own_id) = broadcaster_context(&state).await?;
+    let user_id = client.get_user_id(&login.trim_start_matches('@').to_lowercase()).await.map_err(|e| e.to_string())?;
+    client.add_vip(&own_id, &user_id).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn remove_vip(state: State<'_, AppState>, user_id: String) -> Result<(), String> {
+    let (client, own_id) = broadcaster_context(&state).await?;
+    client.remove_vip(&own_id, &user_id).await.map_err(|e| e.to_string())
+}
+
+/// Whether we may see who follows `channel_id`: our own channel, or one we moderate
+async fn can_see_followers(state: &AppState, channel_id: &str) -> bool {
+    if own_user_id(state).await.is_ok_and(|id| id == channel_id) {
+        return true;
+    }
+    let chat_state = state.chat_state.lock().await;
+    chat_state.channel_login(channel_id).is_some_and(|channel| chat_state.user_state(&channel).is_moderator())
+}
+
+#[tauri::command]
+async fn get_channel_followers(
+    state: State<'_, AppState>,
+    channel: String,
+    after: Option<String>,
+    first: Option<u32>,
+) -> Result<twitch::FollowerPage, String> {
+    let (client, broadcaster_id, _) = moderation_context(&state, &channel).await?;
+    client.get_channel_followers(&broadcaster_id, None, first.unwrap_or(100), after.as_deref()).await.map_err(|e| e.to_string())
+}
+
+/// Follow age of a user in `channel`
+#[tauri::command]
+async fn get_follow_age(state: State<'_, AppState>, channel: String, user_id: String) -> Result<twitch::FollowAge, String> {
+    let (client, broadcaster_id, _) = moderation_context(&state, &channel).await?;
+    if !can_see_followers(&state, &broadcaster_id).await {
+        return Err("Only the broadcaster and moderators can see follow dates".to_string());
+    }
+    let followed_at = client.get_follow_date(&broadcaster_id, &user_id).await.map_err(|e| e.to_string())?;
+    Ok(twitch::FollowAge::new(user_id, followed_at))
+}
+
+#[tauri::command]
+async fn get_chat_settings(state: State<'_, AppState>, channel: String) -> Result<ChatSettings, String> {
+    let (client, broadcaster_id, moderator_id) = moderation_context(&state, &channel).await?;
+    client.get_chat_settings(&broadcaster_id, &moderator_id).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn update_chat_settings(state: State<'_, AppState>, channel: String, settings: ChatSettingsUpdate) -> Result<ChatSettings, String> {
+    let slow_secs = slash_commands::MIN_SLOW_SECS..=slash_commands::MAX_SLOW_SECS;
+    if settings.slow_mode_wait_time.is_some_and(|secs| !slow_secs.contains(&secs)) {
+        return Err("Slow mode wait time must be between 3 and 120 seconds".to_string());
+    }
+    if settings.non_moderator_chat_delay_duration.is_some_and(|d| ![2, 4, 6].contains(&d)) {
+        return Err("Non-moderator chat delay must be 2, 4 or 6 seconds".to_string());
+    }
+    let (client, broadcaster_id, moderator_id) = moderation_context(&state, &channel).await?;
+    client.update_chat_settings(&broadcaster_id, &moderator_id, &settings).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn delete_chat_message(state: State<'_, AppState>, channel: String, message_id: String) -> Result<(), String> {
+    let (client, broadcaster_id, moderator_id) = moderation_context(&state, &channel).await?;
+    client.delete_chat_messages(&broadcaster_id, &moderator_id, Some(&message_id)).await.map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 async fn get_top_streams(state: State<'_, AppState>, limit: Option<u32>) -> Result<serde_json::Value, String> {
     let client = state.twitch_client.lock().await.clone();
@@ -486,6 +1459,22 @@ pub fn run() {
             let store = app.store("settings.bin")?;
             let device_id = store.get("device_id").and_then(|v| v.as_str().map(|s| s.to_string()));
             let access_token = store.get("access_token").and_then(|v| v.as_str().map(|s| s.to_string()));
+            let send_via_helix = store.get("send_via_helix").and_then(|v| v.as_bool()).unwrap_or(false);
+            let recent_messages = store.get("recent_messages")
+                .and_then(|v| serde_json::from_value(v).ok())
+                .unwrap_or_default();
+            let chat_log_settings: chat_log::ChatLogSettings = store.get("chat_logs")
+                .and_then(|v| serde_json::from_value(v).ok())
+                .unwrap_or_default();
+            let chat_log = match app.path().app_data_dir() {
+                Ok(dir) => chat_log::ChatLog::open(&dir.join("chat_logs.sqlite"))
+                    .map_err(|e| error!("[ChatLog] Failed to open chat logs: {}", e))
+                    .ok(),
+                Err(e) => {
+                    error!("[ChatLog] No app data directory: {}", e);
+                    None
+                }
+            };
             
             // Create client (token will be validated asynchronously)
             let client = TwitchClient::new(access_token.clone(), device_id.clone());
@@ -500,10 +1489,32 @@ pub fn run() {
             app.manage(AppState {
                 twitch_client: Mutex::new(client),
                 http_client,
-                chat_handle: Mutex::new(None),
-                chat_sender: Mutex::new(None),
+                chat_connection: Mutex::new(None),
                 watch_state: Mutex::new(None),
                 cached_username: Mutex::new(None),
+                cached_user_id: Mutex::new(None),
+                chat_state: Arc::new(Mutex::new(chat::ChatState {
+                    chat_log: chat_log.clone().filter(|_| chat_log_settings.enabled),
+                    ..Default::default()
+                })),
+                eventsub: Mutex::new(None),
+                whispers: Mutex::new(Default::default()),
+                automod_queue: Mutex::new(Default::default()),
+                send_via_helix: Mutex::new(send_via_helix),
+                recent_messages: Mutex::new(recent_messages),
+                chat_log,
+                chat_log_settings: Mutex::new(chat_log_settings),
+                vod_chat: Mutex::new(None),
+            });
+
+            // Apply the log retention now and then daily while the app runs
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let mut interval = tokio::time::interval(std::time::Duration::from_secs(24 * 60 * 60));
+                loop {
+                    interval.tick().await;
+                    prune_chat_logs(&handle.state::<AppState>()).await;
+                }
             });
 
             // Validate token on startup
@@ -516,6 +1527,9 @@ pub fn run() {
                     match client.get_self_info().await {
                         Ok(_) => {
                             info!("Stored token is valid");
+                            if let Err(e) = start_eventsub(&handle).await {
+                                error!("Failed to start EventSub: {}", e);
+                            }
                         }
                         Err(e) => {
                             info!("Stored token is invalid: {}, clearing...", e);
@@ -554,12 +1568,27 @@ pub fn run() {
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
-            get_stream_url, connect_to_chat, send_chat_message,
+            get_stream_url, join_chat, part_chat, get_joined_channels, send_chat_message,
+            send_chat_reply, get_reply_thread, get_send_via_helix, set_send_via_helix,
+            get_recent_messages_settings, set_recent_messages_settings,
+            get_chat_log_settings, set_chat_log_settings, search_chat_logs, get_chat_log_days, export_chat_logs,
+            sync_vod_chat, stop_vod_chat,
+            get_room_state, get_user_state, get_chatters, get_user_card, get_chat_history,
             get_user_info, get_users_info, get_self_info, get_followed_channels,
             get_channel_emotes, get_global_emotes, get_global_badges, get_channel_badges,
             get_twitch_global_emotes, get_twitch_channel_emotes,
             login, logout, is_logged_in, update_watch_state, set_access_token,
             search_channels, follow_channel, unfollow_channel, get_top_streams,
+            ban_user, timeout_user, unban_user, delete_chat_message,
+            get_chat_settings, update_chat_settings,
+            send_announcement, send_shoutout, start_raid, cancel_raid,
+            get_channel_information, update_channel_information, search_categories,
+            start_commercial, create_stream_marker,
+            get_channel_followers, get_follow_age,
+            get_moderators, add_moderator, remove_moderator, get_vips, add_vip, remove_vip,
+            get_chat_color, set_chat_color,
+            get_automod_queue, approve_automod_message, deny_automod_message,
+            send_whisper, get_whisper_conversations, get_whisper_conversation, mark_whispers_read,
             show_main_window
         ])
         .run(tauri::generate_context!())
@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Twitch counts messages over a rolling 30 seconds
+const RATE_WINDOW: Duration = Duration::from_secs(30);
+/// Messages per window for regular chatters
+const NORMAL_LIMIT: u32 = 20;
+/// Messages per window in channels where we are broadcaster, moderator or VIP
+const PRIVILEGED_LIMIT: u32 = 100;
+/// Twitch rejects a message identical to our previous one in the same channel within this delay
+const DUPLICATE_WINDOW: Duration = Duration::from_secs(30);
+/// Appended to a repeated message so Twitch doesn't treat it as a duplicate (invisible in chat)
+const DUPLICATE_BYPASS: &str = " \u{E0000}";
+
+/// Classic token bucket: `capacity` tokens, refilled continuously over `period`
+struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(capacity: u32, period: Duration) -> Self {
+        Self {
+            capacity: capacity as f64,
+            tokens: capacity as f64,
+            refill_per_sec: capacity as f64 / period.as_secs_f64(),
+            last_refill: Instant::now(),
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+    }
+
+    /// Time until a token is available, zero if one is available now
+    fn wait(&mut self, now: Instant) -> Duration {
+        self.refill(now);
+        if self.tokens >= 1.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((1.0 - self.tokens) / self.refill_per_sec)
+        }
+    }
+
+    fn take(&mut self, now: Instant) {
+        self.refill(now);
+        self.tokens = (self.tokens - 1.0).max(0.0);
+    }
+}
+
+/// Keeps outgoing chat messages within Twitch limits: global message rate, slow mode and duplicates
+pub struct RateLimiter {
+    normal: TokenBucket,
+    privileged: TokenBucket,
+    /// Last message actually written per channel, with its time
+    last_sent: HashMap<String, (String, Instant)>,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self {
+            normal: TokenBucket::new(NORMAL_LIMIT, RATE_WINDOW),
+            privileged: TokenBucket::new(PRIVILEGED_LIMIT, RATE_WINDOW),
+            last_sent: HashMap::new(),
+        }
+    }
+
+    /// How long a message to `channel` has to wait.
+    /// Privileged messages only draw from the 100/30s bucket and ignore slow mode;
+    /// regular ones need both buckets since they count toward either limit.
+    pub fn wait(&mut self, channel: &str, privileged: bool, slow_secs: u32, now: Instant) -> Duration {
+        let rate_wait = if privileged {
+            self.privileged.wait(now)
+        } else {
+            self.normal.wait(now).max(self.privileged.wait(now))
+        };
+
+        let slow_wait = match self.last_sent.get(channel) {
+            Some((_, at)) if !privileged && slow_secs > 0 => {
+                (*at + Duration::from_secs(slow_secs as u64)).saturating_duration_since(now)
+            }
+            _ => Duration::ZERO,
+        };
+
+        rate_wait.max(slow_wait)
+    }
+
+    /// The text to write for `text`, made unique if it repeats our previous message in `channel`
+    pub fn prepare(&self, channel: &str, text: &str, now: Instant) -> String {
+        match self.last_sent.get(channel) {
+            Some((last, at))
+                if now.saturating_duration_since(*at) < DUPLICATE_WINDOW
+                    && last.strip_suffix(DUPLICATE_BYPASS).unwrap_or(last) == text =>
+            {
+                // Alternate between the plain and suffixed text so each differs from the previous one
+                if last.ends_with(DUPLICATE_BYPASS) {
+                    text.to_string()
+                } else {
+                    format!("{}{}", text, DUPLICATE_BYPASS)
+                }
+            }
+            _ => text.to_string(),
+        }
+    }
+
+    /// Record a message written to `channel`
+    pub fn record(&mut self, channel: &str, sent_text: String, privileged: bool, now: Instant) {
+        if !privileged {
+            self.normal.take(now);
+        }
+        self.privileged.take(now);
+        self.last_sent.insert(channel.to_string(), (sent_text, now));
+    }
+}
@@ -0,0 +1,61 @@
+use anyhow::Result;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::chat::{parse_privmsg_line, ChatMessage};
+
+/// Public instance of the robotty recent-messages service
+pub const DEFAULT_RECENT_MESSAGES_URL: &str = "https://recent-messages.robotty.de";
+/// Messages requested when joining a channel
+pub const BACKFILL_LIMIT: usize = 200;
+/// Joining should not wait long on a third-party service
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Backfill settings, stored as `recent_messages` in the settings store
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RecentMessagesSettings {
+    /// Off by default since it sends the channels we join to a third party
+    pub enabled: bool,
+    /// Base URL of a service compatible with the robotty recent-messages API
+    pub base_url: String,
+}
+
+impl Default for RecentMessagesSettings {
+    fn default() -> Self {
+        Self { enabled: false, base_url: DEFAULT_RECENT_MESSAGES_URL.to_string() }
+    }
+}
+
+#[derive(Deserialize)]
+struct RecentMessagesResponse {
+    #[serde(default)]
+    messages: Vec<String>,
+    error: Option<String>,
+}
+
+/// Fetch the messages sent in `channel` before we joined, oldest first, marked historical
+pub async fn fetch_recent_messages(client: &reqwest::Client, base_url: &str, channel: &str, limit: usize) -> Result<Vec<ChatMessage>> {
+    let url = format!(
+        "{}/api/v2/recent-messages/{}?limit={}",
+        base_url.trim_end_matches('/'),
+        urlencoding::encode(&channel.to_lowercase()),
+        limit
+    );
+    let response = client.get(&url).timeout(REQUEST_TIMEOUT).send().await?;
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!("Recent messages error {}", response.status()));
+    }
+    let body: RecentMessagesResponse = response.json().await?;
+    // The service still returns what it has alongside errors such as `channel_not_joined`
+    if let Some(error) = &body.error {
+        info!("[RecentMessages] #{}: {}", channel, error);
+    }
+    Ok(body.messages.iter()
+        .filter_map(|line| parse_privmsg_line(line))
+        .map(|mut message| {
+            message.historical = true;
+            message
+        })
+        .collect())
+}
@@ -0,0 +1,135 @@
+/// Default `/timeout` duration, matching Twitch
+const DEFAULT_TIMEOUT_SECS: u32 = 600;
+/// Longest timeout Twitch accepts (2 weeks)
+pub const MAX_TIMEOUT_SECS: u32 = 1_209_600;
+/// Default `/slow` wait, matching Twitch
+const DEFAULT_SLOW_SECS: u32 = 30;
+/// Slow mode bounds accepted by Helix
+pub const MIN_SLOW_SECS: u32 = 3;
+pub const MAX_SLOW_SECS: u32 = 120;
+
+/// A chat input starting with `/`, to be executed through Helix instead of sent as text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SlashCommand {
+    /// `/me` is still sent over IRC, as a CTCP ACTION
+    Me(String),
+    Ban { user: String, reason: Option<String> },
+    Timeout { user: String, duration_secs: u32, reason: Option<String> },
+    Unban { user: String },
+    Color(String),
+    Whisper { user: String, message: String },
+    Raid(String),
+    Announce { message: String, color: Option<String> },
+    Slow(Option<u32>),
+    Clear,
+}
+
+/// Parse chat input. Returns `None` for plain messages, `Some(Err(usage))` for bad commands.
+pub fn parse(input: &str) -> Option<Result<SlashCommand, String>> {
+    let input = input.trim();
+    let rest = input.strip_prefix('/')?;
+
+    let (name, args) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    let args = args.trim();
+    let mut words = args.split_whitespace();
+    let first = words.next().map(strip_at);
+    // Everything after the first argument, as typed
+    let remainder = args.split_once(char::is_whitespace).map(|(_, r)| r.trim()).filter(|r| !r.is_empty());
+
+    let command = match name.to_lowercase().as_str() {
+        "me" => match args {
+            "" => Err(usage("/me <message>")),
+            _ => Ok(SlashCommand::Me(args.to_string())),
+        },
+        "ban" => match first {
+            Some(user) => Ok(SlashCommand::Ban { user, reason: remainder.map(String::from) }),
+            None => Err(usage("/ban <user> [reason]")),
+        },
+        "timeout" => match first {
+            Some(user) => {
+                // The duration is optional, so a non-duration second word starts the reason
+                let second = words.next();
+                match second.map(parse_duration) {
+                    Some(Some(secs)) if secs == 0 || secs > MAX_TIMEOUT_SECS => {
+                        Err("Timeout duration must be between 1 second and 2 weeks".to_string())
+                    }
+                    Some(Some(secs)) => {
+                        let reason = remainder
+                            .and_then(|r| r.split_once(char::is_whitespace))
+                            .map(|(_, reason)| reason.trim().to_string())
+                            .filter(|r| !r.is_empty());
+                        Ok(SlashCommand::Timeout { user, duration_secs: secs, reason })
+                    }
+                    _ => Ok(SlashCommand::Timeout {
+                        user,
+                        duration_secs: DEFAULT_TIMEOUT_SECS,
+                        reason: remainder.map(String::from),
+                    }),
+                }
+            }
+            None => Err(usage("/timeout <user> [duration] [reason]")),
+        },
+        "unban" | "untimeout" => match first {
+            Some(user) => Ok(SlashCommand::Unban { user }),
+            None => Err(usage(&format!("/{} <user>", name))),
+        },
+        "color" => match args.split_whitespace().next() {
+            Some(color) => Ok(SlashCommand::Color(color.to_string())),
+            None => Err(usage("/color <color name or #hex>")),
+        },
+        "w" | "whisper" => match (first, remainder) {
+            (Some(user), Some(message)) => Ok(SlashCommand::Whisper { user, message: message.to_string() }),
+            _ => Err(usage("/w <user> <message>")),
+        },
+        "raid" => match first {
+            Some(channel) => Ok(SlashCommand::Raid(channel)),
+            None => Err(usage("/raid <channel>")),
+        },
+        "announce" | "announceblue" | "announcegreen" | "announceorange" | "announcepurple" => {
+            let color = name.to_lowercase().strip_prefix("announce").filter(|c| !c.is_empty()).map(String::from);
+            match args {
+                "" => Err(usage(&format!("/{} <message>", name))),
+                _ => Ok(SlashCommand::Announce { message: args.to_string(), color }),
+            }
+        }
+        "slow" => match first {
+            None => Ok(SlashCommand::Slow(Some(DEFAULT_SLOW_SECS))),
+            Some(secs) => match secs.parse::<u32>() {
+                Ok(secs) if (MIN_SLOW_SECS..=MAX_SLOW_SECS).contains(&secs) => Ok(SlashCommand::Slow(Some(secs))),
+                _ => Err(usage("/slow [seconds between 3 and 120]")),
+            },
+        },
+        "slowoff" => Ok(SlashCommand::Slow(None)),
+        "clear" => Ok(SlashCommand::Clear),
+        _ => Err(format!("Unknown command: /{}", name)),
+    };
+    Some(command)
+}
+
+fn usage(syntax: &str) -> String {
+    format!("Usage: {}", syntax)
+}
+
+/// Logins are often typed as mentions
+fn strip_at(word: &str) -> String {
+    word.trim_start_matches('@').to_lowercase()
+}
+
+/// Parse a Twitch-style duration: `600`, `30s`, `10m`, `2h`, `1d` or `1w`
+fn parse_duration(value: &str) -> Option<u32> {
+    let value = value.to_lowercase();
+    let (number, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => value.split_at(i),
+        None => (value.as_str(), "s"),
+    };
+    let number: u32 = number.parse().ok()?;
+    let multiplier = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3_600,
+        "d" => 86_400,
+        "w" => 604_800,
+        _ => return None,
+    };
+    number.checked_mul(multiplier)
+}
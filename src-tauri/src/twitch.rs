@@ -1,6 +1,7 @@
 use log::info;
 use serde::{Deserialize, Serialize};
 use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, ACCEPT};
+use reqwest::StatusCode;
 use anyhow::Result;
 use uuid::Uuid;
 
@@ -11,8 +12,40 @@ pub const GQL_CLIENT_ID: &str = "kd1unb4b3q4t58fwlpcbzcbnm76a8fp";
 pub const CLIENT_ID: &str = "jm293pd1wulfgmdfb8lsw2nkjp2717";
 pub const HELIX_API_URL: &str = "https://api.twitch.tv/helix";
 pub const GQL_URL: &str = "https://gql.twitch.tv/gql/";
+/// Colors Helix accepts for announcements
+pub const ANNOUNCEMENT_COLORS: [&str; 5] = ["primary", "blue", "green", "orange", "purple"];
+/// Chat colors available to everyone, with the hex value chat shows for them
+pub const NAMED_CHAT_COLORS: [(&str, &str); 15] = [
+    ("blue", "#0000FF"), ("blue_violet", "#8A2BE2"), ("cadet_blue", "#5F9EA0"),
+    ("chocolate", "#D2691E"), ("coral", "#FF7F50"), ("dodger_blue", "#1E90FF"),
+    ("firebrick", "#B22222"), ("golden_rod", "#DAA520"), ("green", "#008000"),
+    ("hot_pink", "#FF69B4"), ("orange_red", "#FF4500"), ("red", "#FF0000"),
+    ("sea_green", "#2E8B57"), ("spring_green", "#00FF7F"), ("yellow_green", "#9ACD32"),
+];
 pub const CHROME_UA: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36";
 
+/// Resolve a typed color to the value Helix expects and its hex form.
+/// Names match loosely (`BlueViolet`, `blue_violet`); `#RRGGBB` is for Turbo/Prime users.
+pub fn resolve_chat_color(input: &str) -> Result<(String, String)> {
+    let input = input.trim();
+    if let Some(hex) = input.strip_prefix('#') {
+        if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(anyhow::anyhow!("Invalid hex color '{}', expected #RRGGBB", input));
+        }
+        let hex = format!("#{}", hex.to_uppercase());
+        return Ok((hex.clone(), hex));
+    }
+    let key: String = input.chars().filter(|c| c.is_ascii_alphanumeric()).collect::<String>().to_lowercase();
+    NAMED_CHAT_COLORS.iter()
+        .find(|(name, _)| name.replace('_', "") == key)
+        .map(|(name, hex)| (name.to_string(), hex.to_string()))
+        .ok_or_else(|| anyhow::anyhow!(
+            "Unknown color '{}'. Available: {} (Turbo and Prime users can also use #RRGGBB)",
+            input,
+            NAMED_CHAT_COLORS.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(", ")
+        ))
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GQLResponse<T> {
     pub data: Option<T>,
@@ -36,6 +69,177 @@ pub struct AccessToken {
     pub value: String,
 }
 
+/// Chat settings of a channel, as returned by Helix
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatSettings {
+    pub broadcaster_id: String,
+    pub slow_mode: bool,
+    /// Seconds between messages, `None` when slow mode is off
+    pub slow_mode_wait_time: Option<u32>,
+    pub follower_mode: bool,
+    /// Minimum follow time in minutes, `None` when follower mode is off
+    pub follower_mode_duration: Option<u32>,
+    pub subscriber_mode: bool,
+    pub emote_mode: bool,
+    pub unique_chat_mode: bool,
+    /// Only visible to moderators
+    #[serde(default)]
+    pub non_moderator_chat_delay: Option<bool>,
+    /// Delay in seconds (2, 4 or 6), only visible to moderators
+    #[serde(default)]
+    pub non_moderator_chat_delay_duration: Option<u32>,
+}
+
+/// Chat settings to change; fields left to `None` keep their current value
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChatSettingsUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slow_mode: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slow_mode_wait_time: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub follower_mode: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub follower_mode_duration: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subscriber_mode: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub emote_mode: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unique_chat_mode: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub non_moderator_chat_delay: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub non_moderator_chat_delay_duration: Option<u32>,
+}
+
+/// Stream information of a channel, from Helix `/channels`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelInformation {
+    pub broadcaster_id: String,
+    pub broadcaster_login: String,
+    pub title: String,
+    pub game_id: String,
+    pub game_name: String,
+    pub broadcaster_language: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Stream information to change; fields left to `None` keep their current value
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChannelInformationUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Category id, `"0"` or empty to clear it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub game_id: Option<String>,
+    /// ISO 639-1 code, or `"other"`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub broadcaster_language: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Category {
+    pub id: String,
+    pub name: String,
+    pub box_art_url: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Commercial {
+    /// Length actually started, in seconds
+    pub length: u32,
+    pub message: String,
+    /// Seconds before another commercial can run
+    pub retry_after: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamMarker {
+    pub id: String,
+    pub created_at: String,
+    pub description: String,
+    /// Offset in the stream, in seconds
+    pub position_seconds: u64,
+}
+
+/// A user in a Helix list such as moderators or VIPs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelUser {
+    pub user_id: String,
+    pub user_login: String,
+    pub user_name: String,
+}
+
+/// One page of a paginated Helix user list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserPage {
+    pub users: Vec<ChannelUser>,
+    /// Cursor of the next page, `None` on the last one
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Follower {
+    pub user_id: String,
+    pub user_login: String,
+    pub user_name: String,
+    /// RFC 3339 follow date
+    pub followed_at: String,
+}
+
+/// One page of a channel's followers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FollowerPage {
+    /// Empty unless we moderate the channel; Twitch only gives everyone the total
+    pub followers: Vec<Follower>,
+    pub total: u32,
+    /// Cursor of the next page, `None` on the last one
+    pub cursor: Option<String>,
+}
+
+/// How long a user has followed a channel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FollowAge {
+    pub user_id: String,
+    /// RFC 3339 follow date, `None` when not following
+    pub followed_at: Option<String>,
+    /// Seconds since the follow
+    pub follow_age_secs: Option<i64>,
+}
+
+impl FollowAge {
+    pub fn new(user_id: String, followed_at: Option<String>) -> Self {
+        let follow_age_secs = followed_at.as_deref()
+            .and_then(|date| chrono::DateTime::parse_from_rfc3339(date).ok())
+            .map(|date| (chrono::Utc::now() - date.with_timezone(&chrono::Utc)).num_seconds());
+        FollowAge { user_id, followed_at, follow_age_secs }
+    }
+}
+
+/// An error status from Helix with its explanation, for callers that react to the status
+#[derive(Debug)]
+pub struct HelixError {
+    pub status: StatusCode,
+    pub message: String,
+}
+
+impl std::fmt::Display for HelixError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.status {
+            StatusCode::UNAUTHORIZED => write!(f, "Not authorized (missing scope or expired login): {}", self.message),
+            StatusCode::FORBIDDEN => write!(f, "Permission denied: {}", self.message),
+            StatusCode::TOO_MANY_REQUESTS => write!(f, "Too many requests, try again later: {}", self.message),
+            _ => write!(f, "Helix API error {}: {}", self.status, self.message),
+        }
+    }
+}
+
+impl std::error::Error for HelixError {}
+
 #[derive(Clone)]
 pub struct TwitchClient {
     pub client: reqwest::Client,
@@ -87,6 +291,26 @@ impl TwitchClient {
         headers
     }
 
+    /// Send a Helix request and decode its JSON body (`Null` for empty responses).
+    /// Error statuses become a `HelixError` carrying Helix's explanation.
+    async fn helix_send(&self, request: reqwest::RequestBuilder) -> Result<serde_json::Value> {
+        let res = request.headers(self.helix_headers()).send().await?;
+        let status = res.status();
+        let body = res.text().await?;
+
+        if !status.is_success() {
+            let message = serde_json::from_str::<serde_json::Value>(&body).ok()
+                .and_then(|v| v.get("message").and_then(|m| m.as_str()).map(|m| m.to_string()))
+                .unwrap_or(body);
+            return Err(HelixError { status, message }.into());
+        }
+
+        if body.trim().is_empty() {
+            return Ok(serde_json::Value::Null);
+        }
+        Ok(serde_json::from_str(&body)?)
+    }
+
     pub async fn get_playback_access_token(&self, login: &str) -> Result<AccessToken> {
         let payload = serde_json::json!({
             "operationName": "PlaybackAccessToken",
@@ -163,6 +387,74 @@ impl TwitchClient {
         Err(anyhow::anyhow!("User not found: {:?}", gql_res.errors))
     }
 
+    /// Profile details shown on a user card
+    pub async fn get_user_card_info(&self, login: &str) -> Result<serde_json::Value> {
+        let query = r#"
+            query GetUserCard($login: String!) {
+                user(login: $login) {
+                    id
+                    login
+                    displayName
+                    description
+                    createdAt
+                    profileImageURL(width: 300)
+                }
+            }
+        "#;
+
+        let payload = serde_json::json!({
+            "query": query,
+            "variables": { "login": login }
+        });
+
+        let res = self.client.post(GQL_URL)
+            .headers(self.gql_headers())
+            .json(&payload)
+            .send()
+            .await?;
+
+        let gql_res = res.json::<GQLResponse<serde_json::Value>>().await?;
+        if let Some(data) = gql_res.data {
+            if !data["user"].is_null() {
+                return Ok(data["user"].clone());
+            }
+        }
+        Err(anyhow::anyhow!("User not found: {:?}", gql_res.errors))
+    }
+
+    /// A page of VOD chat, starting at `content_offset_seconds` or continuing from `cursor`.
+    /// Returns the `video` object with its `creator` and `comments` connection.
+    pub async fn get_video_comments(&self, video_id: &str, content_offset_seconds: u32, cursor: Option<&str>) -> Result<serde_json::Value> {
+        let variables = match cursor {
+            Some(cursor) => serde_json::json!({ "videoID": video_id, "cursor": cursor }),
+            None => serde_json::json!({ "videoID": video_id, "contentOffsetSeconds": content_offset_seconds }),
+        };
+        let payload = serde_json::json!({
+            "operationName": "VideoCommentsByOffsetOrCursor",
+            "variables": variables,
+            "extensions": {
+                "persistedQuery": {
+                    "version": 1,
+                    "sha256Hash": "b70a3591ff0f4e0313d126c6a1502d79a1c02baebb288227c582044aa76adf6a"
+                }
+            }
+        });
+
+        let res = self.client.post(GQL_URL)
+            .headers(self.gql_headers())
+            .json(&payload)
+            .send()
+            .await?;
+
+        let gql_res = res.json::<GQLResponse<serde_json::Value>>().await?;
+        if let Some(data) = gql_res.data {
+            if !data["video"].is_null() {
+                return Ok(data["video"].clone());
+            }
+        }
+        Err(anyhow::anyhow!("Video not found: {:?}", gql_res.errors))
+    }
+
     pub async fn get_users_info(&self, logins: Vec<String>) -> Result<serde_json::Value> {
         let query = r#"
             query GetUsers($logins: [String!]) {
@@ -671,4 +963,309 @@ impl TwitchClient {
         }
         Err(anyhow::anyhow!("GQL Error: {:?}", gql_res.errors))
     }
+
+    /// Resolve a login to a user id using Helix
+    pub async fn get_user_id(&self, login: &str) -> Result<String> {
+        let url = format!("{}/users?login={}", HELIX_API_URL, urlencoding::encode(login));
+        let data = self.helix_send(self.client.get(&url)).await?;
+        data.get("data").and_then(|d| d.get(0)).and_then(|u| u.get("id")).and_then(|id| id.as_str())
+            .map(|id| id.to_string())
+            .ok_or_else(|| anyhow::anyhow!("User not found: {}", login))
+    }
+
+    /// Ban a user, or time them out when `duration` (seconds) is set
+    pub async fn ban_user(
+        &self,
+        broadcaster_id: &str,
+        moderator_id: &str,
+        user_id: &str,
+        duration: Option<u32>,
+        reason: Option<&str>,
+    ) -> Result<()> {
+        let url = format!("{}/moderation/bans?broadcaster_id={}&moderator_id={}", HELIX_API_URL, broadcaster_id, moderator_id);
+        let mut data = serde_json::json!({ "user_id": user_id });
+        if let Some(duration) = duration {
+            data["duration"] = duration.into();
+        }
+        if let Some(reason) = reason {
+            data["reason"] = reason.into();
+        }
+        self.helix_send(self.client.post(&url).json(&serde_json::json!({ "data": data }))).await?;
+        Ok(())
+    }
+
+    /// Lift a ban or timeout
+    pub async fn unban_user(&self, broadcaster_id: &str, moderator_id: &str, user_id: &str) -> Result<()> {
+        let url = format!("{}/moderation/bans?broadcaster_id={}&moderator_id={}&user_id={}",
+            HELIX_API_URL, broadcaster_id, moderator_id, user_id);
+        self.helix_send(self.client.delete(&url)).await?;
+        Ok(())
+    }
+
+    /// Delete one message, or clear the whole chat when `message_id` is `None`
+    pub async fn delete_chat_messages(&self, broadcaster_id: &str, moderator_id: &str, message_id: Option<&str>) -> Result<()> {
+        let mut url = format!("{}/moderation/chat?broadcaster_id={}&moderator_id={}", HELIX_API_URL, broadcaster_id, moderator_id);
+        if let Some(message_id) = message_id {
+            url.push_str(&format!("&message_id={}", urlencoding::encode(message_id)));
+        }
+        self.helix_send(self.client.delete(&url)).await?;
+        Ok(())
+    }
+
+    /// Read the chat settings of a channel; moderator-only fields are filled when we moderate it
+    pub async fn get_chat_settings(&self, broadcaster_id: &str, moderator_id: &str) -> Result<ChatSettings> {
+        let url = format!("{}/chat/settings?broadcaster_id={}&moderator_id={}", HELIX_API_URL, broadcaster_id, moderator_id);
+        let data = self.helix_send(self.client.get(&url)).await?;
+        Self::first_item(data, "chat settings")
+    }
+
+    /// One page (up to 1000) of users connected to a channel's chat; moderators only
+    pub async fn get_chatters(&self, broadcaster_id: &str, moderator_id: &str, first: u32, after: Option<&str>) -> Result<serde_json::Value> {
+        let mut url = format!("{}/chat/chatters?broadcaster_id={}&moderator_id={}&first={}",
+            HELIX_API_URL, broadcaster_id, moderator_id, first.clamp(1, 1000));
+        if let Some(after) = after {
+            url.push_str(&format!("&after={}", urlencoding::encode(after)));
+        }
+        self.helix_send(self.client.get(&url)).await
+    }
+
+    /// Update chat settings and return the resulting ones
+    pub async fn update_chat_settings(&self, broadcaster_id: &str, moderator_id: &str, settings: &ChatSettingsUpdate) -> Result<ChatSettings> {
+        let url = format!("{}/chat/settings?broadcaster_id={}&moderator_id={}", HELIX_API_URL, broadcaster_id, moderator_id);
+        let data = self.helix_send(self.client.patch(&url).json(settings)).await?;
+        Self::first_item(data, "chat settings")
+    }
+
+    /// Current chat name color as `#RRGGBB`, `None` if the user never picked one
+    pub async fn get_chat_color(&self, user_id: &str) -> Result<Option<String>> {
+        let url = format!("{}/chat/color?user_id={}", HELIX_API_URL, user_id);
+        let data = self.helix_send(self.client.get(&url)).await?;
+        Ok(data.get("data").and_then(|d| d.get(0)).and_then(|c| c.get("color")).and_then(|c| c.as_str())
+            .filter(|c| !c.is_empty())
+            .map(|c| c.to_string()))
+    }
+
+    /// Set the chat name color: a named color, or `#RRGGBB` for Turbo/Prime users
+    pub async fn update_chat_color(&self, user_id: &str, color: &str) -> Result<()> {
+        let url = format!("{}/chat/color?user_id={}&color={}", HELIX_API_URL, user_id, urlencoding::encode(color));
+        self.helix_send(self.client.put(&url)).await?;
+        Ok(())
+    }
+
+    pub async fn send_whisper(&self, from_user_id: &str, to_user_id: &str, message: &str) -> Result<()> {
+        let url = format!("{}/whispers?from_user_id={}&to_user_id={}", HELIX_API_URL, from_user_id, to_user_id);
+        self.helix_send(self.client.post(&url).json(&serde_json::json!({ "message": message }))).await?;
+        Ok(())
+    }
+
+    /// Send a chat message through Helix. Unlike IRC, the answer says right away whether
+    /// it was sent (`is_sent`) or why it was dropped (`drop_reason`).
+    pub async fn send_chat_message(
+        &self,
+        broadcaster_id: &str,
+        sender_id: &str,
+        message: &str,
+        reply_parent_id: Option<&str>,
+    ) -> Result<serde_json::Value> {
+        let url = format!("{}/chat/messages", HELIX_API_URL);
+        let mut body = serde_json::json!({
+            "broadcaster_id": broadcaster_id,
+            "sender_id": sender_id,
+            "message": message,
+        });
+        if let Some(parent_id) = reply_parent_id {
+            body["reply_parent_message_id"] = parent_id.into();
+        }
+        let data = self.helix_send(self.client.post(&url).json(&body)).await?;
+        data.get("data").and_then(|d| d.get(0)).cloned()
+            .ok_or_else(|| anyhow::anyhow!("Helix returned no send result"))
+    }
+
+    /// Approve (`allow`) or deny a message held by AutoMod
+    pub async fn manage_held_automod_message(&self, moderator_id: &str, message_id: &str, allow: bool) -> Result<()> {
+        let url = format!("{}/moderation/automod/message", HELIX_API_URL);
+        let body = serde_json::json!({
+            "user_id": moderator_id,
+            "msg_id": message_id,
+            "action": if allow { "ALLOW" } else { "DENY" },
+        });
+        self.helix_send(self.client.post(&url).json(&body)).await?;
+        Ok(())
+    }
+
+    /// Subscribe an EventSub WebSocket session to an event type and return the subscription id
+    pub async fn create_eventsub_subscription(&self, kind: &str, version: &str, condition: &serde_json::Value, session_id: &str) -> Result<String> {
+        let url = format!("{}/eventsub/subscriptions", HELIX_API_URL);
+        let body = serde_json::json!({
+            "type": kind,
+            "version": version,
+            "condition": condition,
+            "transport": { "method": "websocket", "session_id": session_id },
+        });
+        let response = self.helix_send(self.client.post(&url).json(&body)).await?;
+        response["data"][0]["id"].as_str()
+            .map(|id| id.to_string())
+            .ok_or_else(|| anyhow::anyhow!("EventSub subscription created without an id"))
+    }
+
+    pub async fn delete_eventsub_subscription(&self, id: &str) -> Result<()> {
+        let url = format!("{}/eventsub/subscriptions?id={}", HELIX_API_URL, urlencoding::encode(id));
+        self.helix_send(self.client.delete(&url)).await?;
+        Ok(())
+    }
+
+    /// Start a raid from `from_broadcaster_id` to `to_broadcaster_id`
+    pub async fn start_raid(&self, from_broadcaster_id: &str, to_broadcaster_id: &str) -> Result<serde_json::Value> {
+        let url = format!("{}/raids?from_broadcaster_id={}&to_broadcaster_id={}", HELIX_API_URL, from_broadcaster_id, to_broadcaster_id);
+        let data = self.helix_send(self.client.post(&url)).await?;
+        Ok(data.get("data").and_then(|d| d.get(0)).cloned().unwrap_or_default())
+    }
+
+    /// Cancel a pending raid of `broadcaster_id`
+    pub async fn cancel_raid(&self, broadcaster_id: &str) -> Result<()> {
+        let url = format!("{}/raids?broadcaster_id={}", HELIX_API_URL, broadcaster_id);
+        self.helix_send(self.client.delete(&url)).await?;
+        Ok(())
+    }
+
+    /// Shout out `to_broadcaster_id` in `from_broadcaster_id`'s chat
+    pub async fn send_shoutout(&self, from_broadcaster_id: &str, to_broadcaster_id: &str, moderator_id: &str) -> Result<()> {
+        let url = format!("{}/chat/shoutouts?from_broadcaster_id={}&to_broadcaster_id={}&moderator_id={}",
+            HELIX_API_URL, from_broadcaster_id, to_broadcaster_id, moderator_id);
+        self.helix_send(self.client.post(&url)).await?;
+        Ok(())
+    }
+
+    /// Send an announcement; `color` is blue, green, orange, purple or `None` for the channel accent
+    pub async fn send_announcement(&self, broadcaster_id: &str, moderator_id: &str, message: &str, color: Option<&str>) -> Result<()> {
+        let url = format!("{}/chat/announcements?broadcaster_id={}&moderator_id={}", HELIX_API_URL, broadcaster_id, moderator_id);
+        let color = color.unwrap_or("primary").to_lowercase();
+        if !ANNOUNCEMENT_COLORS.contains(&color.as_str()) {
+            return Err(anyhow::anyhow!("Invalid announcement color '{}', expected one of: {}", color, ANNOUNCEMENT_COLORS.join(", ")));
+        }
+        let body = serde_json::json!({ "message": message, "color": color });
+        self.helix_send(self.client.post(&url).json(&body)).await?;
+        Ok(())
+    }
+
+    pub async fn get_channel_information(&self, broadcaster_id: &str) -> Result<ChannelInformation> {
+        let url = format!("{}/channels?broadcaster_id={}", HELIX_API_URL, broadcaster_id);
+        let data = self.helix_send(self.client.get(&url)).await?;
+        Self::first_item(data, "channel information")
+    }
+
+    pub async fn modify_channel_information(&self, broadcaster_id: &str, update: &ChannelInformationUpdate) -> Result<()> {
+        let url = format!("{}/channels?broadcaster_id={}", HELIX_API_URL, broadcaster_id);
+        self.helix_send(self.client.patch(&url).json(update)).await?;
+        Ok(())
+    }
+
+    /// Categories (games) matching `query`, for the category picker
+    pub async fn search_categories(&self, query: &str, first: u32) -> Result<Vec<Category>> {
+        let url = format!("{}/search/categories?query={}&first={}", HELIX_API_URL, urlencoding::encode(query), first.clamp(1, 100));
+        let mut data = self.helix_send(self.client.get(&url)).await?;
+        let categories = data.get_mut("data").map(serde_json::Value::take).unwrap_or_default();
+        Ok(serde_json::from_value(categories)?)
+    }
+
+    /// Run a commercial of `length` seconds (Twitch rounds to 30, 60, 90, 120, 150 or 180)
+    pub async fn start_commercial(&self, broadcaster_id: &str, length: u32) -> Result<Commercial> {
+        let url = format!("{}/channels/commercial", HELIX_API_URL);
+        let body = serde_json::json!({ "broadcaster_id": broadcaster_id, "length": length });
+        let data = self.helix_send(self.client.post(&url).json(&body)).await?;
+        Self::first_item(data, "commercial")
+    }
+
+    /// Mark the current position of a live stream
+    pub async fn create_stream_marker(&self, user_id: &str, description: Option<&str>) -> Result<StreamMarker> {
+        let url = format!("{}/streams/markers", HELIX_API_URL);
+        let mut body = serde_json::json!({ "user_id": user_id });
+        if let Some(description) = description {
+            body["description"] = description.into();
+        }
+        let data = self.helix_send(self.client.post(&url).json(&body)).await?;
+        Self::first_item(data, "stream marker")
+    }
+
+    pub async fn get_moderators(&self, broadcaster_id: &str, first: u32, after: Option<&str>) -> Result<UserPage> {
+        let url = format!("{}/moderation/moderators?broadcaster_id={}", HELIX_API_URL, broadcaster_id);
+        self.get_user_page(&url, first, after).await
+    }
+
+    pub async fn add_moderator(&self, broadcaster_id: &str, user_id: &str) -> Result<()> {
+        let url = format!("{}/moderation/moderators?broadcaster_id={}&user_id={}", HELIX_API_URL, broadcaster_id, user_id);
+        self.helix_send(self.client.post(&url)).await?;
+        Ok(())
+    }
+
+    pub async fn remove_moderator(&self, broadcaster_id: &str, user_id: &str) -> Result<()> {
+        let url = format!("{}/moderation/moderators?broadcaster_id={}&user_id={}", HELIX_API_URL, broadcaster_id, user_id);
+        self.helix_send(self.client.delete(&url)).await?;
+        Ok(())
+    }
+
+    pub async fn get_vips(&self, broadcaster_id: &str, first: u32, after: Option<&str>) -> Result<UserPage> {
+        let url = format!("{}/channels/vips?broadcaster_id={}", HELIX_API_URL, broadcaster_id);
+        self.get_user_page(&url, first, after).await
+    }
+
+    pub async fn add_vip(&self, broadcaster_id: &str, user_id: &str) -> Result<()> {
+        let url = format!("{}/channels/vips?broadcaster_id={}&user_id={}", HELIX_API_URL, broadcaster_id, user_id);
+        self.helix_send(self.client.post(&url)).await?;
+        Ok(())
+    }
+
+    pub async fn remove_vip(&self, broadcaster_id: &str, user_id: &str) -> Result<()> {
+        let url = format!("{}/channels/vips?broadcaster_id={}&user_id={}", HELIX_API_URL, broadcaster_id, user_id);
+        self.helix_send(self.client.delete(&url)).await?;
+        Ok(())
+    }
+
+    /// Followers of a channel, most recent first, or only `user_id` when set
+    pub async fn get_channel_followers(&self, broadcaster_id: &str, user_id: Option<&str>, first: u32, after: Option<&str>) -> Result<FollowerPage> {
+        let mut url = format!("{}/channels/followers?broadcaster_id={}&first={}", HELIX_API_URL, broadcaster_id, first.clamp(1, 100));
+        if let Some(user_id) = user_id {
+            url.push_str(&format!("&user_id={}", user_id));
+        }
+        if let Some(after) = after {
+            url.push_str(&format!("&after={}", urlencoding::encode(after)));
+        }
+        let mut data = self.helix_send(self.client.get(&url)).await?;
+        let followers = data.get_mut("data").map(serde_json::Value::take).unwrap_or_default();
+        let cursor = data.get("pagination").and_then(|p| p.get("cursor")).and_then(|c| c.as_str())
+            .filter(|c| !c.is_empty())
+            .map(|c| c.to_string());
+        Ok(FollowerPage {
+            followers: serde_json::from_value(followers)?,
+            total: data.get("total").and_then(|t| t.as_u64()).unwrap_or(0) as u32,
+            cursor,
+        })
+    }
+
+    /// When `user_id` followed the channel, `None` if they don't follow it
+    pub async fn get_follow_date(&self, broadcaster_id: &str, user_id: &str) -> Result<Option<String>> {
+        let page = self.get_channel_followers(broadcaster_id, Some(user_id), 1, None).await?;
+        Ok(page.followers.into_iter().next().map(|f| f.followed_at))
+    }
+
+    /// Fetch a page of a Helix user list; `url` already holds its own query parameters
+    async fn get_user_page(&self, url: &str, first: u32, after: Option<&str>) -> Result<UserPage> {
+        let mut url = format!("{}&first={}", url, first.clamp(1, 100));
+        if let Some(after) = after {
+            url.push_str(&format!("&after={}", urlencoding::encode(after)));
+        }
+        let mut data = self.helix_send(self.client.get(&url)).await?;
+        let users = data.get_mut("data").map(serde_json::Value::take).unwrap_or_default();
+        let cursor = data.get("pagination").and_then(|p| p.get("cursor")).and_then(|c| c.as_str())
+            .filter(|c| !c.is_empty())
+            .map(|c| c.to_string());
+        Ok(UserPage { users: serde_json::from_value(users)?, cursor })
+    }
+
+    /// Deserialize `data[0]` of a Helix response
+    fn first_item<T: serde::de::DeserializeOwned>(mut data: serde_json::Value, what: &str) -> Result<T> {
+        let item = data.get_mut("data").and_then(|d| d.get_mut(0)).map(serde_json::Value::take)
+            .ok_or_else(|| anyhow::anyhow!("Helix returned no {}", what))?;
+        Ok(serde_json::from_value(item)?)
+    }
 }
@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+
+use crate::chat::ChatMessage;
+
+/// Everything shown when clicking a chatter
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UserCard {
+    pub user_id: String,
+    pub login: String,
+    pub display_name: String,
+    pub description: Option<String>,
+    pub profile_image_url: Option<String>,
+    /// RFC 3339 account creation date
+    pub created_at: Option<String>,
+    /// Whether we can see follows of this channel (broadcaster or moderator); the follow fields
+    /// are `None` when we can't, whether or not the user follows
+    pub follow_visible: bool,
+    /// `None` when not following, or when not `follow_visible`
+    pub followed_at: Option<String>,
+    pub follow_age_secs: Option<i64>,
+    /// From the badges of the user's last message, `None` if not subscribed or never seen
+    pub subscription: Option<SubscriptionInfo>,
+    /// Badges of the user's last message in this channel
+    pub badges: Vec<(String, String)>,
+    /// Messages the user sent in this channel this session, oldest first
+    pub recent_messages: Vec<ChatMessage>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SubscriptionInfo {
+    /// 1, 2 or 3
+    pub tier: u32,
+    /// Cumulative months, when the badge info exposes it
+    pub months: Option<u32>,
+    pub founder: bool,
+}
+
+impl SubscriptionInfo {
+    /// Read subscription status from chat badges. The subscriber badge version encodes the tier
+    /// (e.g. `2012` is tier 2); `badge-info` carries the exact month count.
+    pub fn from_badges(badges: &[(String, String)], badge_info: &[(String, String)]) -> Option<Self> {
+        let (set, version) = badges.iter().find(|(set, _)| set == "subscriber" || set == "founder")?;
+        let tier = match version.parse::<u32>().unwrap_or(0) {
+            v if v >= 3000 => 3,
+            v if v >= 2000 => 2,
+            _ => 1,
+        };
+        let months = badge_info.iter()
+            .find(|(name, _)| name == "subscriber" || name == "founder")
+            .and_then(|(_, months)| months.parse().ok());
+        Some(SubscriptionInfo { tier, months, founder: set == "founder" })
+    }
+}
@@ -0,0 +1,216 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+
+use crate::chat::ChatMessage;
+use crate::twitch::TwitchClient;
+
+/// Chat kept fetched ahead of the playback position
+const PREFETCH_AHEAD_SECS: f64 = 30.0;
+/// Pages fetched per sync at most, so a slow API never stalls playback updates for long
+const MAX_PAGES_PER_SYNC: usize = 3;
+/// Forward jumps larger than this are seeks rather than playback
+const SEEK_THRESHOLD_SECS: f64 = 10.0;
+/// Backward jitter tolerated before treating a position as a seek
+const BACKWARD_TOLERANCE_SECS: f64 = 1.0;
+/// Chat shown from before the new position after a seek
+const SEEK_CONTEXT_SECS: f64 = 10.0;
+/// Already played comments kept for short backward seeks
+const MAX_PLAYED_COMMENTS: usize = 500;
+
+/// A VOD chat message and when it was sent, relative to the start of the video
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VodComment {
+    pub offset_seconds: f64,
+    pub message: ChatMessage,
+}
+
+/// Result of `sync_vod_chat`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VodChatUpdate {
+    pub video_id: String,
+    /// The position jumped; comments shown so far should be cleared
+    pub reset: bool,
+    /// Comments that became due since the previous sync, oldest first
+    pub comments: Vec<VodComment>,
+}
+
+/// Comments of one video around the playback position.
+///
+/// Every comment with an offset in `buffered_from..=buffered_until` is in `comments`, sorted by offset.
+/// Offsets are whole seconds, which the bounds rely on.
+pub struct VodChatReplay {
+    pub video_id: String,
+    comments: Vec<VodComment>,
+    ids: HashSet<String>,
+    /// Index of the first comment not yet returned
+    next: usize,
+    buffered_from: f64,
+    buffered_until: f64,
+    /// Continues after the last buffered comment, `None` at the end of the video
+    cursor: Option<String>,
+    /// Position of the previous sync, `None` before the first one
+    position: Option<f64>,
+}
+
+impl VodChatReplay {
+    pub fn new(video_id: &str) -> Self {
+        Self {
+            video_id: video_id.to_string(),
+            comments: Vec::new(),
+            ids: HashSet::new(),
+            next: 0,
+            buffered_from: 0.0,
+            buffered_until: -1.0,
+            cursor: None,
+            position: None,
+        }
+    }
+
+    /// Advance to `position` (seconds into the video) and return the comments now due.
+    /// Seeks outside the buffer refetch from the new position.
+    pub async fn sync(&mut self, client: &TwitchClient, position: f64) -> Result<VodChatUpdate> {
+        let position = position.max(0.0);
+        let reset = match self.position {
+            None => true,
+            Some(previous) => position < previous - BACKWARD_TOLERANCE_SECS || position > previous + SEEK_THRESHOLD_SECS,
+        };
+
+        if reset {
+            let from = (position - SEEK_CONTEXT_SECS).max(0.0).floor();
+            if from < self.buffered_from || position > self.buffered_until {
+                self.refetch(client, from).await?;
+            }
+            self.next = self.comments.partition_point(|c| c.offset_seconds < from);
+        }
+        self.position = Some(position);
+
+        for _ in 0..MAX_PAGES_PER_SYNC {
+            if self.cursor.is_none() || self.buffered_until >= position + PREFETCH_AHEAD_SECS {
+                break;
+            }
+            self.fetch_next(client).await?;
+        }
+
+        // Comments past `buffered_until` may still miss some sent in the same second
+        let due = position.min(self.buffered_until);
+        let end = self.comments.partition_point(|c| c.offset_seconds <= due).max(self.next);
+        let comments = self.comments[self.next..end].to_vec();
+        self.next = end;
+        self.drop_played();
+
+        Ok(VodChatUpdate { video_id: self.video_id.clone(), reset, comments })
+    }
+
+    async fn refetch(&mut self, client: &TwitchClient, from: f64) -> Result<()> {
+        self.comments.clear();
+        self.ids.clear();
+        self.next = 0;
+        self.buffered_from = from;
+        self.buffered_until = from - 1.0;
+        self.cursor = None;
+        let video = client.get_video_comments(&self.video_id, from as u32, None).await?;
+        self.add_page(&video);
+        Ok(())
+    }
+
+    async fn fetch_next(&mut self, client: &TwitchClient) -> Result<()> {
+        let Some(cursor) = self.cursor.clone() else { return Ok(()) };
+        // The cursor stays in place when the request fails, so the next sync retries it
+        let video = client.get_video_comments(&self.video_id, 0, Some(&cursor)).await?;
+        self.add_page(&video);
+        // A page that adds nothing would make us ask for it forever
+        if self.cursor.as_deref() == Some(cursor.as_str()) {
+            self.cursor = None;
+            self.buffered_until = f64::INFINITY;
+        }
+        Ok(())
+    }
+
+    fn add_page(&mut self, video: &Value) {
+        let channel = video["creator"]["login"].as_str().unwrap_or_default();
+        let connection = &video["comments"];
+        let edges = connection["edges"].as_array().map(Vec::as_slice).unwrap_or_default();
+        for node in edges.iter().map(|edge| &edge["node"]) {
+            if let Some(comment) = parse_comment(node, channel) {
+                if self.ids.insert(comment.message.id.clone()) {
+                    let index = self.comments.partition_point(|c| c.offset_seconds <= comment.offset_seconds);
+                    self.comments.insert(index, comment);
+                }
+            }
+        }
+
+        let has_next = connection["pageInfo"]["hasNextPage"].as_bool().unwrap_or(false);
+        let cursor = edges.last().and_then(|edge| edge["cursor"].as_str()).filter(|c| !c.is_empty());
+        match (has_next, cursor) {
+            (true, Some(cursor)) => {
+                self.cursor = Some(cursor.to_string());
+                // The next page may hold more comments from the last second of this one
+                if let Some(last) = self.comments.last() {
+                    self.buffered_until = self.buffered_until.max(last.offset_seconds - 1.0);
+                }
+            }
+            _ => {
+                self.cursor = None;
+                self.buffered_until = f64::INFINITY;
+            }
+        }
+    }
+
+    /// Forget comments played long ago, keeping the buffer bounds true
+    fn drop_played(&mut self) {
+        if self.next <= MAX_PLAYED_COMMENTS {
+            return;
+        }
+        let count = self.next - MAX_PLAYED_COMMENTS;
+        for comment in self.comments.drain(..count) {
+            self.ids.remove(&comment.message.id);
+            // Others from the same second may remain, so only the next second is complete
+            self.buffered_from = comment.offset_seconds + 1.0;
+        }
+        self.next -= count;
+    }
+}
+
+/// A `VideoComment` GQL node as a chat message
+fn parse_comment(node: &Value, channel: &str) -> Option<VodComment> {
+    let id = node["id"].as_str()?.to_string();
+    let offset_seconds = node["contentOffsetSeconds"].as_f64()?;
+    let commenter = &node["commenter"];
+    let login = commenter["login"].as_str().unwrap_or_default().to_string();
+    let message = &node["message"];
+    let text: String = message["fragments"].as_array()
+        .map(|fragments| fragments.iter().filter_map(|f| f["text"].as_str()).collect())
+        .unwrap_or_default();
+    let badges = message["userBadges"].as_array()
+        .map(|badges| badges.iter()
+            .filter_map(|b| Some((b["setID"].as_str()?.to_string(), b["version"].as_str()?.to_string())))
+            .filter(|(set, _)| !set.is_empty())
+            .collect())
+        .unwrap_or_default();
+    let timestamp = node["createdAt"].as_str()
+        .and_then(|created| chrono::DateTime::parse_from_rfc3339(created).ok())
+        .map(|created| created.timestamp_millis())
+        .unwrap_or_default();
+
+    Some(VodComment {
+        offset_seconds,
+        message: ChatMessage {
+            id,
+            user: commenter["displayName"].as_str().filter(|n| !n.is_empty()).unwrap_or(&login).to_string(),
+            login,
+            user_id: commenter["id"].as_str().unwrap_or_default().to_string(),
+            message: text,
+            color: message["userColor"].as_str().filter(|c| !c.is_empty()).map(|c| c.to_string()),
+            badges,
+            badge_info: Vec::new(),
+            channel: channel.to_string(),
+            timestamp,
+            reply: None,
+            is_action: false,
+            historical: false,
+            tags: Default::default(),
+        },
+    })
+}
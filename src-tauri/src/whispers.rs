@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Messages kept per conversation, oldest dropped first
+const MAX_CONVERSATION_MESSAGES: usize = 200;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Whisper {
+    pub id: String,
+    pub from_user_id: String,
+    pub from_login: String,
+    pub from_display_name: String,
+    pub text: String,
+    /// Unix time in milliseconds
+    pub timestamp: i64,
+    /// Sent by us rather than received
+    pub outgoing: bool,
+}
+
+/// Whispers exchanged with one user
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Conversation {
+    pub user_id: String,
+    pub login: String,
+    pub display_name: String,
+    pub messages: Vec<Whisper>,
+    pub unread: u32,
+}
+
+/// Unread whisper counts, sent with the `whisper-unread` event
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct WhisperUnread {
+    pub total: u32,
+    /// Unread count per user id, only for conversations with unread whispers
+    pub by_user: HashMap<String, u32>,
+}
+
+/// Whisper conversations of this session, keyed by the other user's id
+#[derive(Default)]
+pub struct WhisperStore {
+    conversations: HashMap<String, Conversation>,
+}
+
+impl WhisperStore {
+    /// Store a `user.whisper.message` EventSub event
+    pub fn add_incoming(&mut self, event: &serde_json::Value) -> Option<Whisper> {
+        let field = |name: &str| event.get(name).and_then(|v| v.as_str()).map(|s| s.to_string());
+        let whisper = Whisper {
+            id: field("whisper_id").unwrap_or_default(),
+            from_user_id: field("from_user_id")?,
+            from_login: field("from_user_login").unwrap_or_default(),
+            from_display_name: field("from_user_name").unwrap_or_default(),
+            text: event.get("whisper").and_then(|w| w.get("text")).and_then(|t| t.as_str())?.to_string(),
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            outgoing: false,
+        };
+        let conversation = self.conversation_mut(&whisper.from_user_id, &whisper.from_login, &whisper.from_display_name);
+        conversation.unread += 1;
+        Self::push(conversation, whisper.clone());
+        Some(whisper)
+    }
+
+    /// Store a whisper we sent to `to_user_id`
+    pub fn add_outgoing(
+        &mut self,
+        to_user_id: &str,
+        to_login: &str,
+        from_user_id: &str,
+        from_login: &str,
+        from_display_name: &str,
+        text: &str,
+    ) -> Whisper {
+        let whisper = Whisper {
+            id: uuid::Uuid::new_v4().to_string(),
+            from_user_id: from_user_id.to_string(),
+            from_login: from_login.to_string(),
+            from_display_name: from_display_name.to_string(),
+            text: text.to_string(),
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            outgoing: true,
+        };
+        let conversation = self.conversation_mut(to_user_id, to_login, to_login);
+        // Answering a conversation means it has been read
+        conversation.unread = 0;
+        Self::push(conversation, whisper.clone());
+        whisper
+    }
+
+    fn conversation_mut(&mut self, user_id: &str, login: &str, display_name: &str) -> &mut Conversation {
+        self.conversations.entry(user_id.to_string()).or_insert_with(|| Conversation {
+            user_id: user_id.to_string(),
+            login: login.to_string(),
+            display_name: display_name.to_string(),
+            messages: Vec::new(),
+            unread: 0,
+        })
+    }
+
+    fn push(conversation: &mut Conversation, whisper: Whisper) {
+        conversation.messages.push(whisper);
+        if conversation.messages.len() > MAX_CONVERSATION_MESSAGES {
+            conversation.messages.remove(0);
+        }
+    }
+
+    pub fn mark_read(&mut self, user_id: &str) {
+        if let Some(conversation) = self.conversations.get_mut(user_id) {
+            conversation.unread = 0;
+        }
+    }
+
+    pub fn conversation(&self, user_id: &str) -> Option<Conversation> {
+        self.conversations.get(user_id).cloned()
+    }
+
+    /// All conversations, most recent activity first
+    pub fn conversations(&self) -> Vec<Conversation> {
+        let mut conversations: Vec<Conversation> = self.conversations.values().cloned().collect();
+        conversations.sort_by_key(|c| std::cmp::Reverse(c.messages.last().map(|m| m.timestamp).unwrap_or(0)));
+        conversations
+    }
+
+    pub fn unread(&self) -> WhisperUnread {
+        let by_user: HashMap<String, u32> = self.conversations.values()
+            .filter(|c| c.unread > 0)
+            .map(|c| (c.user_id.clone(), c.unread))
+            .collect();
+        WhisperUnread { total: by_user.values().sum(), by_user }
+    }
+}
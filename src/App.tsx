@@ -331,7 +331,7 @@ export default function App() {
           globalBadges={globalBadges}
           channelBadges={channelBadges}
           isLoggedIn={isLoggedIn}
-          isConnected={chat.isConnected}
+          connectionState={chat.connectionState}
           isAtBottom={chat.isAtBottom}
           chatContainerRef={chat.chatContainerRef}
           chatEndRef={chat.chatEndRef}
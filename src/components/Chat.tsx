@@ -1,7 +1,7 @@
 import { useState, useMemo } from "react";
 import { PanelRight, PanelLeft, User, Settings, Send } from "lucide-react";
 import { cn } from "../lib/utils";
-import type { ChatMessage, TwitchBadge } from "../types";
+import type { ChatMessage, ConnectionState, TwitchBadge } from "../types";
 
 interface ChatProps {
   isOpen: boolean;
@@ -11,7 +11,8 @@ interface ChatProps {
   globalBadges: TwitchBadge[];
   channelBadges: TwitchBadge[];
   isLoggedIn: boolean;
-  isConnected: boolean;
+  /** Messages go through Helix while IRC is reconnecting or failed */
+  connectionState: ConnectionState["state"] | null;
   isAtBottom: boolean;
   chatContainerRef: React.RefObject<HTMLDivElement | null>;
   chatEndRef: React.RefObject<HTMLDivElement | null>;
@@ -28,7 +29,7 @@ export function Chat({
   globalBadges,
   channelBadges,
   isLoggedIn,
-  isConnected,
+  connectionState,
   isAtBottom,
   chatContainerRef,
   chatEndRef,
@@ -107,14 +108,14 @@ export function Chat({
         <div className="p-3 border-t border-border">
           <div className="relative mb-3">
             <textarea
-              placeholder={!isLoggedIn ? "Log in to chat" : !isConnected ? "Connecting to chat..." : "Send a message"}
+              placeholder={!isLoggedIn ? "Log in to chat" : connectionState === "reconnecting" || connectionState === "failed" ? "Chat disconnected, sending through Helix" : "Send a message"}
               value={chatInput}
               onChange={(e) => setChatInput(e.target.value)}
               onKeyDown={handleKeyDown}
-              disabled={!isLoggedIn || !isConnected}
+              disabled={!isLoggedIn}
               className={cn(
                 "w-full bg-base border border-border rounded-md p-2 text-sm focus:outline-none focus:border-twitch resize-none min-h-[44px] max-h-[160px] transition-all placeholder:text-muted/40",
-                (!isLoggedIn || !isConnected) && "opacity-50 cursor-not-allowed"
+                !isLoggedIn && "opacity-50 cursor-not-allowed"
               )}
             />
           </div>
@@ -124,10 +125,10 @@ export function Chat({
             </button>
             <button
               onClick={handleSend}
-              disabled={!isLoggedIn || !isConnected || !chatInput.trim()}
+              disabled={!isLoggedIn || !chatInput.trim()}
               className={cn(
                 "bg-twitch hover:bg-twitch-dark px-4 py-1.5 rounded-md font-bold text-[13px] transition-all shadow-lg shadow-twitch/20 active:scale-95 text-white flex items-center gap-2",
-                (!isLoggedIn || !isConnected || !chatInput.trim()) && "opacity-50 cursor-not-allowed"
+                (!isLoggedIn || !chatInput.trim()) && "opacity-50 cursor-not-allowed"
               )}
             >
               <Send className="w-4 h-4" /> Chat
@@ -184,7 +185,7 @@ function ChatMessageView({ msg, emotes, globalBadges, channelBadges }: ChatMessa
   }, [msg.message, emotes]);
 
   return (
-    <div className="text-[13px] leading-tight break-words py-0.5">
+    <div className={cn("text-[13px] leading-tight break-words py-0.5", msg.historical && "opacity-60")}>
       <span className="text-muted mr-2 text-[11px]">
         {new Date(msg.timestamp).toLocaleTimeString([], { hour: "2-digit", minute: "2-digit" })}
       </span>
@@ -197,9 +198,9 @@ function ChatMessageView({ msg, emotes, globalBadges, channelBadges }: ChatMessa
         className="font-bold hover:bg-hover cursor-pointer rounded px-1 -ml-1 mr-1"
         style={{ color: msg.color || "#ff8280" }}
       >
-        {msg.user}:
+        {msg.user}{msg.is_action ? "" : ":"}
       </span>
-      <span>{parts}</span>
+      <span className={cn(msg.is_action && "italic")} style={msg.is_action ? { color: msg.color || "#ff8280" } : undefined}>{parts}</span>
     </div>
   );
 }
@@ -1,5 +1,8 @@
 export { useAuth } from "./useAuth";
+export { useAutomod } from "./useAutomod";
 export { useChat } from "./useChat";
 export { useEmotes } from "./useEmotes";
 export { useSearch } from "./useSearch";
 export { useTopStreams } from "./useTopStreams";
+export { useVodChat } from "./useVodChat";
+export { useWhispers } from "./useWhispers";
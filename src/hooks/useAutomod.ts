@@ -0,0 +1,56 @@
+import { useState, useEffect, useCallback } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { error as logError } from "@tauri-apps/plugin-log";
+import type { AutomodResolution, HeldMessage } from "../types";
+
+interface UseAutomodReturn {
+  /** Messages of the channel waiting for review, oldest first */
+  heldMessages: HeldMessage[];
+  approve: (messageId: string) => Promise<void>;
+  deny: (messageId: string) => Promise<void>;
+}
+
+export function useAutomod(channelId: string | null): UseAutomodReturn {
+  const [heldMessages, setHeldMessages] = useState<HeldMessage[]>([]);
+
+  useEffect(() => {
+    if (!channelId) {
+      setHeldMessages([]);
+      return;
+    }
+    invoke<HeldMessage[]>("get_automod_queue", { channelId })
+      .then(setHeldMessages)
+      .catch((err) => logError(`[useAutomod] Failed to load queue: ${err}`));
+
+    const unlistenHeld = listen<HeldMessage>("automod-held", (event) => {
+      if (event.payload.broadcaster_user_id !== channelId) return;
+      setHeldMessages((prev) => [...prev, event.payload]);
+    });
+    const unlistenResolved = listen<AutomodResolution>("automod-resolved", (event) => {
+      setHeldMessages((prev) => prev.filter((m) => m.message_id !== event.payload.message_id));
+    });
+    return () => {
+      unlistenHeld.then((f) => f());
+      unlistenResolved.then((f) => f());
+    };
+  }, [channelId]);
+
+  const approve = useCallback(async (messageId: string) => {
+    try {
+      await invoke("approve_automod_message", { messageId });
+    } catch (err) {
+      logError(`[useAutomod] Failed to approve message: ${err}`);
+    }
+  }, []);
+
+  const deny = useCallback(async (messageId: string) => {
+    try {
+      await invoke("deny_automod_message", { messageId });
+    } catch (err) {
+      logError(`[useAutomod] Failed to deny message: ${err}`);
+    }
+  }, []);
+
+  return { heldMessages, approve, deny };
+}
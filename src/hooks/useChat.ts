@@ -2,15 +2,28 @@ import { useState, useEffect, useRef, useCallback } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import { info, debug, error as logError } from "@tauri-apps/plugin-log";
-import type { ChatMessage } from "../types";
+import type { ChatMessage, ConnectionState, QueuedMessageStatus, RoomState, SendResult } from "../types";
+
+/** Messages kept on screen */
+const MAX_MESSAGES = 200;
 
 interface UseChatReturn {
   messages: ChatMessage[];
   isAtBottom: boolean;
   isConnected: boolean;
+  /** Latest "chat-connection-state", null before the first one */
+  connectionState: ConnectionState["state"] | null;
+  roomState: RoomState | null;
+  /** Reason the last message was refused by Twitch, cleared on the next successful send */
+  sendError: string | null;
+  /** Rate-limiter queue status of messages we are still waiting to send, by nonce */
+  queuedMessages: Record<string, QueuedMessageStatus>;
   chatContainerRef: React.RefObject<HTMLDivElement | null>;
   chatEndRef: React.RefObject<HTMLDivElement | null>;
   sendMessage: (message: string) => Promise<void>;
+  sendReply: (parentId: string, message: string) => Promise<void>;
+  /** Prepend older messages from the backend history; resolves to false when there are none left */
+  loadOlderMessages: () => Promise<boolean>;
   handleScroll: () => void;
   scrollToBottom: () => void;
 }
@@ -18,51 +31,78 @@ interface UseChatReturn {
 export function useChat(channel: string | null, isLoggedIn: boolean): UseChatReturn {
   const [messages, setMessages] = useState<ChatMessage[]>([]);
   const [isAtBottom, setIsAtBottom] = useState(true);
-  const [isConnected, setIsConnected] = useState(false);
+  const [connectionState, setConnectionState] = useState<ConnectionState["state"] | null>(null);
+  const isConnected = connectionState === "connected";
+  const [roomState, setRoomState] = useState<RoomState | null>(null);
+  const [sendError, setSendError] = useState<string | null>(null);
+  const [queuedMessages, setQueuedMessages] = useState<Record<string, QueuedMessageStatus>>({});
   const chatContainerRef = useRef<HTMLDivElement>(null);
   const chatEndRef = useRef<HTMLDivElement>(null);
   const currentChannelRef = useRef<string | null>(null);
   const connectingRef = useRef<string | null>(null);
   const messageListenerRef = useRef<(() => void) | null>(null);
-  const disconnectListenerRef = useRef<(() => void) | null>(null);
+  const connectionListenerRef = useRef<(() => void) | null>(null);
 
   // Track seen message IDs to prevent duplicates
   const seenIdsRef = useRef<Set<string>>(new Set());
 
-  // Connect to chat when channel changes
+  // Join chat when the channel changes, leaving the previous one on the shared connection.
+  // Re-joining after a login change lets the backend reconnect with the new account.
   useEffect(() => {
+    const previousChannel = currentChannelRef.current;
+
     if (!channel) {
       currentChannelRef.current = null;
       connectingRef.current = null;
-      setIsConnected(false);
+      setConnectionState(null);
+      setRoomState(null);
       setMessages([]);
       seenIdsRef.current.clear();
+      if (previousChannel) {
+        invoke("part_chat", { channel: previousChannel }).catch(err => logError(`[useChat] Failed to leave chat: ${err}`));
+      }
       return;
     }
     
-    // Guard against duplicate connections
-    if (connectingRef.current === channel) {
+    // Guard against duplicate joins
+    const joinKey = `${channel}:${isLoggedIn}`;
+    if (connectingRef.current === joinKey) {
       return;
     }
-    connectingRef.current = channel;
-    setIsConnected(false);
+    connectingRef.current = joinKey;
+
+    if (previousChannel && previousChannel !== channel) {
+      invoke("part_chat", { channel: previousChannel }).catch(err => logError(`[useChat] Failed to leave chat: ${err}`));
+    }
 
-    info(`[useChat] Connecting to chat: ${channel}`);
+    info(`[useChat] Joining chat: ${channel}`);
     currentChannelRef.current = channel;
-    setMessages([]);
-    seenIdsRef.current.clear();
-    setIsAtBottom(true);
+    if (previousChannel !== channel) {
+      setMessages([]);
+      setRoomState(null);
+      seenIdsRef.current.clear();
+      setIsAtBottom(true);
+    }
 
-    invoke("connect_to_chat", { channel })
-      .then(() => {
-        setIsConnected(true);
+    // The backend owns the connection lifecycle and reports it via "chat-connection-state"
+    invoke("join_chat", { channel })
+      .then(async () => {
+        const [state, history] = await Promise.all([
+          invoke<RoomState | null>("get_room_state", { channel }),
+          invoke<ChatMessage[]>("get_chat_history", { channel, limit: MAX_MESSAGES }),
+        ]);
+        if (currentChannelRef.current !== channel) return;
+        if (state) setRoomState(state);
+        // Messages kept by the backend from earlier visits (or before a webview reload)
+        const older = history.filter((m) => !seenIdsRef.current.has(m.id));
+        older.forEach((m) => seenIdsRef.current.add(m.id));
+        setMessages((prev) => [...older, ...prev].slice(-MAX_MESSAGES));
       })
       .catch(err => {
-        logError(`[useChat] Failed to connect to chat: ${err}`);
+        logError(`[useChat] Failed to join chat: ${err}`);
         connectingRef.current = null;
-        setIsConnected(false);
       });
-  }, [channel]);
+  }, [channel, isLoggedIn]);
 
   // Listen for chat messages
   useEffect(() => {
@@ -94,7 +134,7 @@ export function useChat(channel: string | null, isLoggedIn: boolean): UseChatRet
           }
         }
 
-        setMessages((prev) => [...prev, { ...newMsg, timestamp: Date.now() }].slice(-200));
+        setMessages((prev) => [...prev, newMsg].slice(-MAX_MESSAGES));
       });
       messageListenerRef.current = unlisten;
     };
@@ -109,44 +149,59 @@ export function useChat(channel: string | null, isLoggedIn: boolean): UseChatRet
     };
   }, []);
 
-  // Handle chat disconnection and auto-reconnect
+  // Track room state (slow mode, sub-only, ...) for the current channel
+  useEffect(() => {
+    const unlistenPromise = listen<RoomState>("chat-room-state", (event) => {
+      if (event.payload.channel === currentChannelRef.current) {
+        setRoomState(event.payload);
+      }
+    });
+
+    return () => {
+      unlistenPromise.then((unlisten) => unlisten());
+    };
+  }, []);
+
+  // Track messages held back by the backend rate limiter
+  useEffect(() => {
+    const unlistenPromise = listen<QueuedMessageStatus>("chat-send-queued", (event) => {
+      setQueuedMessages((prev) => ({ ...prev, [event.payload.nonce]: event.payload }));
+    });
+
+    return () => {
+      unlistenPromise.then((unlisten) => unlisten());
+    };
+  }, []);
+
+  // Follow the backend connection lifecycle (it reconnects on its own)
   useEffect(() => {
     // Prevent duplicate listener registration
-    if (disconnectListenerRef.current) return;
-    
+    if (connectionListenerRef.current) return;
+
     const setupListener = async () => {
-      const unlisten = await listen<string>("chat-disconnected", async (event) => {
-        const disconnectedChannel = event.payload;
-        info(`[useChat] Chat disconnected from: ${disconnectedChannel}`);
-
-        if (disconnectedChannel === channel) {
-          setIsConnected(false);
-          info("[useChat] Attempting to reconnect...");
-          await new Promise(resolve => setTimeout(resolve, 2000));
-
-          if (disconnectedChannel === channel) {
-            try {
-              await invoke("connect_to_chat", { channel });
-              setIsConnected(true);
-              info("[useChat] Successfully reconnected");
-            } catch (err) {
-              logError(`[useChat] Failed to reconnect: ${err}`);
-            }
-          }
+      const unlisten = await listen<ConnectionState>("chat-connection-state", (event) => {
+        const state = event.payload;
+        info(`[useChat] Chat connection state: ${state.state}`);
+        setConnectionState(state.state);
+
+        if (state.state === "failed") {
+          logError(`[useChat] Chat connection failed: ${state.reason}`);
+          // Login was rejected; the next join (e.g. after logging in again) opens a new connection
+          connectingRef.current = null;
         }
       });
-      disconnectListenerRef.current = unlisten;
+      connectionListenerRef.current = unlisten;
     };
-    
+
     setupListener();
-    
+
     return () => {
-      if (disconnectListenerRef.current) {
-        disconnectListenerRef.current();
-        disconnectListenerRef.current = null;
+      if (connectionListenerRef.current) {
+        connectionListenerRef.current();
+        connectionListenerRef.current = null;
       }
     };
-  }, [channel]);
+  }, []);
 
   // Track if user manually scrolled
   const userScrolledRef = useRef(false);
@@ -182,23 +237,69 @@ export function useChat(channel: string | null, isLoggedIn: boolean): UseChatRet
     setIsAtBottom(true);
   }, []);
 
-  const sendMessage = useCallback(async (message: string) => {
-    if (!message.trim() || !isLoggedIn || !isConnected) return;
+  const send = useCallback(async (message: string, parentId?: string) => {
+    // The backend falls back to Helix when IRC is down, so being disconnected doesn't block sending
+    if (!channel || !message.trim() || !isLoggedIn) return;
 
+    const nonce = crypto.randomUUID();
     try {
-      await invoke("send_chat_message", { message: message.trim() });
+      const result = parentId
+        ? await invoke<SendResult>("send_chat_reply", { channel, parentId, message: message.trim(), nonce })
+        : await invoke<SendResult>("send_chat_message", { channel, message: message.trim(), nonce });
+      if (result.status === "failed") {
+        logError(`[useChat] Message refused (${result.reason}): ${result.message}`);
+        setSendError(result.message);
+      } else {
+        if (result.status === "executed") info(`[useChat] ${result.message}`);
+        setSendError(null);
+      }
     } catch (err) {
+      // Slash command usage errors and Helix failures land here
       logError(`[useChat] Send message error: ${err}`);
+      setSendError(String(err));
+    } finally {
+      setQueuedMessages((prev) => {
+        const next = { ...prev };
+        delete next[nonce];
+        return next;
+      });
+    }
+  }, [channel, isLoggedIn]);
+
+  const sendMessage = useCallback((message: string) => send(message), [send]);
+  const sendReply = useCallback((parentId: string, message: string) => send(message, parentId), [send]);
+
+  const loadOlderMessages = useCallback(async () => {
+    if (!channel || messages.length === 0) return false;
+    try {
+      const older = await invoke<ChatMessage[]>("get_chat_history", {
+        channel,
+        beforeId: messages[0].id,
+        limit: 100,
+      });
+      if (older.length === 0 || currentChannelRef.current !== channel) return false;
+      older.forEach((m) => seenIdsRef.current.add(m.id));
+      setMessages((prev) => [...older, ...prev]);
+      return true;
+    } catch (err) {
+      logError(`[useChat] Failed to load chat history: ${err}`);
+      return false;
     }
-  }, [isLoggedIn, isConnected]);
+  }, [channel, messages]);
 
   return {
     messages,
     isAtBottom,
     isConnected,
+    connectionState,
+    roomState,
+    sendError,
+    queuedMessages,
     chatContainerRef,
     chatEndRef,
     sendMessage,
+    sendReply,
+    loadOlderMessages,
     handleScroll,
     scrollToBottom,
   };
@@ -0,0 +1,41 @@
+import { useState, useEffect, useRef } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { error as logError } from "@tauri-apps/plugin-log";
+import type { ChatMessage, VodChatUpdate } from "../types";
+
+/** Messages kept on screen */
+const MAX_MESSAGES = 200;
+
+/**
+ * Chat replay of a VOD, following `currentTime` (seconds into the video).
+ * Updates arriving while a sync is running are skipped; the next one catches up.
+ */
+export function useVodChat(videoId: string | null, currentTime: number): ChatMessage[] {
+  const [messages, setMessages] = useState<ChatMessage[]>([]);
+  const syncingRef = useRef(false);
+
+  useEffect(() => {
+    setMessages([]);
+    if (!videoId) return;
+    return () => {
+      invoke("stop_vod_chat").catch((err) => logError(`[useVodChat] Failed to stop replay: ${err}`));
+    };
+  }, [videoId]);
+
+  useEffect(() => {
+    if (!videoId || syncingRef.current) return;
+    syncingRef.current = true;
+    invoke<VodChatUpdate>("sync_vod_chat", { videoId, position: currentTime })
+      .then((update) => {
+        if (update.video_id !== videoId) return;
+        const due = update.comments.map((c) => c.message);
+        setMessages((prev) => (update.reset ? due : [...prev, ...due]).slice(-MAX_MESSAGES));
+      })
+      .catch((err) => logError(`[useVodChat] Failed to sync replay: ${err}`))
+      .finally(() => {
+        syncingRef.current = false;
+      });
+  }, [videoId, currentTime]);
+
+  return messages;
+}
@@ -0,0 +1,60 @@
+import { useState, useEffect, useCallback } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { error as logError } from "@tauri-apps/plugin-log";
+import type { Whisper, WhisperConversation, WhisperUnread } from "../types";
+
+interface UseWhispersReturn {
+  /** Conversations of this session, most recent first */
+  conversations: WhisperConversation[];
+  unread: WhisperUnread;
+  sendWhisper: (toLogin: string, message: string) => Promise<void>;
+  markRead: (userId: string) => Promise<void>;
+}
+
+const NO_UNREAD: WhisperUnread = { total: 0, by_user: {} };
+
+export function useWhispers(isLoggedIn: boolean): UseWhispersReturn {
+  const [conversations, setConversations] = useState<WhisperConversation[]>([]);
+  const [unread, setUnread] = useState<WhisperUnread>(NO_UNREAD);
+
+  const refresh = useCallback(async () => {
+    try {
+      setConversations(await invoke<WhisperConversation[]>("get_whisper_conversations"));
+    } catch (err) {
+      logError(`[useWhispers] Failed to load conversations: ${err}`);
+    }
+  }, []);
+
+  useEffect(() => {
+    if (!isLoggedIn) {
+      setConversations([]);
+      setUnread(NO_UNREAD);
+      return;
+    }
+    refresh();
+
+    const unlistenReceived = listen<Whisper>("whisper-received", () => refresh());
+    const unlistenUnread = listen<WhisperUnread>("whisper-unread", (event) => setUnread(event.payload));
+    return () => {
+      unlistenReceived.then((f) => f());
+      unlistenUnread.then((f) => f());
+    };
+  }, [isLoggedIn, refresh]);
+
+  const sendWhisper = useCallback(async (toLogin: string, message: string) => {
+    if (!message.trim()) return;
+    await invoke<Whisper>("send_whisper", { toLogin, message: message.trim() });
+    await refresh();
+  }, [refresh]);
+
+  const markRead = useCallback(async (userId: string) => {
+    try {
+      setUnread(await invoke<WhisperUnread>("mark_whispers_read", { userId }));
+    } catch (err) {
+      logError(`[useWhispers] Failed to mark whispers read: ${err}`);
+    }
+  }, []);
+
+  return { conversations, unread, sendWhisper, markRead };
+}
@@ -78,12 +78,377 @@ export interface SearchResult {
 /** Chat message from IRC */
 export interface ChatMessage {
   id: string;
+  /** Display name */
   user: string;
+  login: string;
+  user_id: string;
   message: string;
   color?: string;
   badges: [string, string][];
+  /** Extra badge details, e.g. exact subscription months as ["subscriber", "14"] */
+  badge_info: [string, string][];
+  /** Unix time in milliseconds */
   timestamp: number;
   channel: string;
+  /** Set when the message answers another one */
+  reply?: ReplyInfo;
+  /** `/me` message */
+  is_action: boolean;
+  /** Sent before we joined, from the recent-messages backfill */
+  historical: boolean;
+  /** Raw IRC tags, empty for our own echoed messages */
+  tags: Record<string, string>;
+}
+
+/** The message a reply answers, and its thread */
+export interface ReplyInfo {
+  parent_msg_id: string;
+  parent_user_login?: string;
+  parent_display_name?: string;
+  parent_msg_body?: string;
+  /** First message of the thread; replies to replies share it */
+  thread_parent_msg_id: string;
+  thread_parent_user_login?: string;
+}
+
+/** Replies seen this session for one thread, from get_reply_thread */
+export interface ReplyThread {
+  channel: string;
+  thread_parent_msg_id: string;
+  thread_parent_user_login?: string;
+  replies: ChatMessage[];
+}
+
+/** Chat modes of a channel, from ROOMSTATE */
+export interface RoomState {
+  channel: string;
+  room_id?: string;
+  emote_only: boolean;
+  /** Minimum follow time in minutes, absent when followers-only is off */
+  followers_only?: number;
+  r9k: boolean;
+  /** Seconds between messages, 0 when slow mode is off */
+  slow: number;
+  subs_only: boolean;
+  /** Unix timestamp (ms) of our last message in this channel */
+  last_sent_at?: number;
+}
+
+/** Our own chat identity, from GLOBALUSERSTATE/USERSTATE */
+export interface UserState {
+  /** Channel login, absent for the global state */
+  channel?: string;
+  user_id?: string;
+  display_name?: string;
+  color?: string;
+  badges: [string, string][];
+  badge_info: [string, string][];
+  emote_sets: string[];
+  is_mod: boolean;
+}
+
+/** NOTICE from Twitch, from "chat-notice" */
+export interface ChatNotice {
+  /** Channel login, absent for connection-wide notices */
+  channel?: string;
+  msg_id?: string;
+  message: string;
+}
+
+/** Chat connection lifecycle, from "chat-connection-state" */
+export type ConnectionState =
+  | { state: "connecting" }
+  | { state: "connected" }
+  | { state: "reconnecting"; attempt: number; delay_ms: number }
+  /** Credentials were rejected; network errors are retried as "reconnecting" instead */
+  | { state: "failed"; reason: string };
+
+/** Why Twitch refused a chat message */
+export type SendFailure =
+  | "rate_limited"
+  | "duplicate"
+  | "banned"
+  | "timed_out"
+  | "warned"
+  | "suspended"
+  | "channel_suspended"
+  | "followers_only"
+  | "subs_only"
+  | "emote_only"
+  | "slow_mode"
+  | "unique_chat"
+  | "verified_only"
+  | "automod_held"
+  | "blocked_term"
+  | "rejected"
+  | "no_response"
+  | "disconnected";
+
+/** A message held back by the rate limiter, from "chat-send-queued" */
+export interface QueuedMessageStatus {
+  nonce: string;
+  channel: string;
+  /** 1-based position in the outgoing queue */
+  position: number;
+  /** Estimated wait before the message is written */
+  wait_ms: number;
+}
+
+/** Result of send_chat_message */
+export type SendResult =
+  | { status: "sent"; message_id: string }
+  | { status: "failed"; reason: SendFailure; message: string }
+  /** A slash command ran through Helix instead of being sent */
+  | { status: "executed"; message: string };
+
+/** Recent-messages backfill on join, from get_recent_messages_settings */
+export interface RecentMessagesSettings {
+  enabled: boolean;
+  /** Base URL of a robotty recent-messages compatible service */
+  base_url: string;
+}
+
+// ============================================
+// Chat Log Types
+// ============================================
+
+/** From get_chat_log_settings */
+export interface ChatLogSettings {
+  /** Off by default */
+  enabled: boolean;
+  /** Days logs are kept, null to keep them forever */
+  retention_days: number | null;
+}
+
+/** Filters of search_chat_logs, all optional */
+export interface ChatLogQuery {
+  /** Matched as a phrase */
+  text?: string;
+  /** Author login */
+  user?: string;
+  channel?: string;
+  /** Unix time in milliseconds, inclusive */
+  from?: number;
+  /** Unix time in milliseconds, exclusive */
+  to?: number;
+  limit?: number;
+}
+
+/** A channel and local day with logs, from get_chat_log_days */
+export interface ChatLogDay {
+  channel: string;
+  /** YYYY-MM-DD */
+  day: string;
+  message_count: number;
+}
+
+/** Formats of export_chat_logs; "chatterino" writes one file per day under the chosen directory */
+export type ExportFormat = "text" | "json_lines" | "chatterino";
+
+/** From export_chat_logs */
+export interface ExportSummary {
+  message_count: number;
+  files: string[];
+}
+
+// ============================================
+// Moderation Types
+// ============================================
+
+/** Chat settings of the watched channel, from Helix */
+export interface ChatSettings {
+  broadcaster_id: string;
+  slow_mode: boolean;
+  slow_mode_wait_time: number | null;
+  follower_mode: boolean;
+  /** Minimum follow time in minutes */
+  follower_mode_duration: number | null;
+  subscriber_mode: boolean;
+  emote_mode: boolean;
+  unique_chat_mode: boolean;
+  /** Only visible to moderators */
+  non_moderator_chat_delay: boolean | null;
+  non_moderator_chat_delay_duration: number | null;
+}
+
+export interface SubscriptionInfo {
+  tier: number;
+  months: number | null;
+  founder: boolean;
+}
+
+/** Everything shown when clicking a chatter, from get_user_card */
+export interface UserCard {
+  user_id: string;
+  login: string;
+  display_name: string;
+  description: string | null;
+  profile_image_url: string | null;
+  created_at: string | null;
+  /** Whether we can see follows of this channel (broadcaster or moderator) */
+  follow_visible: boolean;
+  /** Null when not following, or when not follow_visible */
+  followed_at: string | null;
+  follow_age_secs: number | null;
+  subscription: SubscriptionInfo | null;
+  badges: [string, string][];
+  recent_messages: ChatMessage[];
+}
+
+/** Fields to change with update_chat_settings; omitted fields keep their value */
+export type ChatSettingsUpdate = Partial<Omit<ChatSettings, "broadcaster_id">>;
+
+export type ChatterRole = "broadcaster" | "moderator" | "vip" | "viewer";
+
+export interface Chatter {
+  user_id: string;
+  login: string;
+  display_name: string;
+  role: ChatterRole;
+}
+
+/** Users in a channel grouped by role, from get_chatters */
+export interface ChatterList {
+  broadcaster: Chatter[];
+  moderators: Chatter[];
+  vips: Chatter[];
+  viewers: Chatter[];
+  total: number;
+  /** Pass as `after` to get the next page */
+  cursor: string | null;
+  /** False when only users seen talking are known (not a moderator) */
+  from_helix: boolean;
+}
+
+/** A chat message AutoMod is holding for review */
+export interface HeldMessage {
+  message_id: string;
+  broadcaster_user_id: string;
+  broadcaster_user_login: string;
+  user_id: string;
+  user_login: string;
+  user_name: string;
+  text: string;
+  category: string;
+  level: number;
+  held_at: string;
+}
+
+export type AutomodStatus = "approved" | "denied" | "expired";
+
+/** Payload of the "automod-resolved" event */
+export interface AutomodResolution {
+  message_id: string;
+  broadcaster_user_id: string;
+  status: AutomodStatus;
+  moderator_login: string | null;
+}
+
+export interface Follower {
+  user_id: string;
+  user_login: string;
+  user_name: string;
+  /** RFC 3339 follow date */
+  followed_at: string;
+}
+
+/** One page of the watched channel's followers */
+export interface FollowerPage {
+  /** Empty unless we moderate the channel */
+  followers: Follower[];
+  total: number;
+  cursor: string | null;
+}
+
+export interface FollowAge {
+  user_id: string;
+  /** Null when the user doesn't follow the channel */
+  followed_at: string | null;
+  follow_age_secs: number | null;
+}
+
+// ============================================
+// Broadcaster Types
+// ============================================
+
+/** Stream information of our channel */
+export interface ChannelInformation {
+  broadcaster_id: string;
+  broadcaster_login: string;
+  title: string;
+  game_id: string;
+  game_name: string;
+  broadcaster_language: string;
+  tags: string[];
+}
+
+/** Fields to change with update_channel_information; omitted fields keep their value */
+export type ChannelInformationUpdate = Partial<Pick<ChannelInformation, "title" | "game_id" | "broadcaster_language" | "tags">>;
+
+export interface Category {
+  id: string;
+  name: string;
+  box_art_url: string;
+}
+
+export interface Commercial {
+  length: number;
+  message: string;
+  /** Seconds before another commercial can run */
+  retry_after: number;
+}
+
+export interface StreamMarker {
+  id: string;
+  created_at: string;
+  description: string;
+  position_seconds: number;
+}
+
+/** A user in a moderators or VIPs list */
+export interface ChannelUser {
+  user_id: string;
+  user_login: string;
+  user_name: string;
+}
+
+/** One page of a paginated user list */
+export interface UserPage {
+  users: ChannelUser[];
+  /** Pass as `after` to get the next page */
+  cursor: string | null;
+}
+
+// ============================================
+// Whisper Types
+// ============================================
+
+export interface Whisper {
+  id: string;
+  from_user_id: string;
+  from_login: string;
+  from_display_name: string;
+  text: string;
+  /** Unix time in milliseconds */
+  timestamp: number;
+  /** Sent by us rather than received */
+  outgoing: boolean;
+}
+
+/** Whispers exchanged with one user */
+export interface WhisperConversation {
+  user_id: string;
+  login: string;
+  display_name: string;
+  messages: Whisper[];
+  unread: number;
+}
+
+/** Payload of the "whisper-unread" event */
+export interface WhisperUnread {
+  total: number;
+  /** Unread count per user id */
+  by_user: Record<string, number>;
 }
 
 // ============================================
@@ -180,6 +545,21 @@ export interface QualityLevel {
   height: number;
 }
 
+/** A VOD chat message and when it was sent, in seconds into the video */
+export interface VodComment {
+  offset_seconds: number;
+  message: ChatMessage;
+}
+
+/** From sync_vod_chat */
+export interface VodChatUpdate {
+  video_id: string;
+  /** Playback jumped; clear the comments shown so far */
+  reset: boolean;
+  /** Comments due since the previous sync, oldest first */
+  comments: VodComment[];
+}
+
 // ============================================
 // UI State Types
 // ============================================